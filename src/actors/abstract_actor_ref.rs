@@ -21,6 +21,12 @@ pub trait AbstractActorRef: fmt::Display {
     fn cell(self: &mut Self) -> TSafe<ActorCell>;
     fn clone(self: &Self) -> ActorRef;
     fn as_any(self: &Self) -> Box<Any>;
+
+    /// Is reference points to the actor of the local actor system. Cell may be taken only from
+    /// the local references.
+    fn is_local(self: &Self) -> bool {
+        true
+    }
 }
 
 
@@ -2,10 +2,15 @@ use crate::actors::actor_ref_factory::ActorRefFactory;
 use crate::actors::abstract_actor_ref::ActorRef;
 use crate::actors::watcher::WatchingEvents;
 use crate::actors::scheduler::Scheduler;
+use crate::actors::event_stream::EventStream;
+use crate::actors::coordinated_shutdown::CoordinatedShutdown;
 use crate::actors::dispatcher::Dispatcher;
+use crate::actors::mailbox::MailboxFactory;
 use crate::common::tsafe::TSafe;
+use crate::futures::future::WrappedFuture;
 use crate::executors::executor::Executor;
 use std::collections::HashMap;
+use std::time::Duration;
 
 //TODO docs
 pub trait AbstractActorSystem: ActorRefFactory {
@@ -13,11 +18,35 @@ pub trait AbstractActorSystem: ActorRefFactory {
     /// Returns actor system scheduler
     fn get_scheduler(&self) -> TSafe<Scheduler>;
 
+    /// Returns actor system event stream
+    fn event_stream(&self) -> EventStream;
+
+    /// Returns total count of messages dropped to the dead letters
+    fn dead_letters_count(&self) -> usize;
+
+    /// Enables or disables logging of unhandled messages with warning level. Disabled by default.
+    fn set_log_unhandled(&mut self, enabled: bool);
+
+    /// Is unhandled messages is logged
+    fn is_log_unhandled(&self) -> bool;
+
     /// Register new watching event from the specified actor
     fn register_watch_event(&self, from: &ActorRef, event: WatchingEvents);
 
-    /// Stops the actor system
-    fn terminate(&mut self);
+    /// Stops the actor system through the coordinated shutdown. Blocks the caller until all phases
+    /// will be completed and the actor system will be stopped. Must not be called from an actor,
+    /// because his own stopping will wait for the end of this call. Use terminate_async there.
+    fn terminate(&mut self) {
+        let mut done = self.terminate_async();
+        while !done.ready(Duration::from_secs(1)) {}
+    }
+
+    /// Same as terminate, but does not block the caller. Shutdown is run in the separate thread,
+    /// returned future is completed after the actor system was stopped.
+    fn terminate_async(&mut self) -> WrappedFuture<(), ()>;
+
+    /// Returns coordinated shutdown of the system
+    fn coordinated_shutdown(&self) -> TSafe<CoordinatedShutdown>;
 
     /// Adds new dispatcher to the system. For now supporter only default dispatcher replacing
     fn add_dispatcher(&mut self, name: &str, dispatcher: TSafe<Dispatcher + Send>);
@@ -28,6 +57,12 @@ pub trait AbstractActorSystem: ActorRefFactory {
     /// Returns dispatchers list
     fn get_dispatchers(&self) -> TSafe<HashMap<String, TSafe<Dispatcher + Send>>>;
 
+    /// Adds new mailbox factory to the system under specified name
+    fn add_mailbox(&mut self, name: &str, factory: MailboxFactory);
+
+    /// Returns mailboxes factories list
+    fn get_mailboxes(&self) -> TSafe<HashMap<String, MailboxFactory>>;
+
     /// Returns dispatcher by name as executor
     fn get_executor(&self, name: &str) -> TSafe<Executor + Send>;
 
@@ -3,18 +3,24 @@
 //! This trait must be realized by the structure, which want to be involved to the system as
 //! a particular actor.
 use crate::actors::actor_context::ActorContext;
-use crate::actors::message::Message;
+use crate::actors::message::{Message, ControlMessage};
 use crate::actors::error::Error;
 use crate::actors::supervision::SupervisionStrategy;
+use crate::common::tsafe::TSafe;
 
 use std::any::Any;
 
 pub type HandleResult = Result<bool, Error>;
 
+/// Message handler which may replace the receive function of an actor at runtime. See
+/// become_ / unbecome methods of the ActorContext for more details.
+pub type Behavior = TSafe<FnMut(Message, ActorContext) -> HandleResult + Send>;
+
 pub trait Actor {
     fn pre_start(&mut self, _ctx: ActorContext) {}
     fn post_stop(&mut self, _ctx: ActorContext) {}
     fn pre_fail(&mut self, _ctx: ActorContext, err: Error, strategy: SupervisionStrategy) {}
+    fn pre_restart(&mut self, _ctx: ActorContext, _err: Error, _msg: Option<Message>) {}
     fn post_restart(&mut self, _ctx: ActorContext) {}
     fn receive(&mut self, msg: Message, ctx: ActorContext) -> HandleResult;
     fn as_any(&mut self) -> &Any {
@@ -25,3 +31,5 @@ pub trait Actor {
 /// Service message. Stops the actor which will receive him. See  actors lifetime management
 /// articles in the main doc, for more details, about how this message works.
 pub struct PoisonPill {}
+
+impl ControlMessage for PoisonPill {}
@@ -6,20 +6,24 @@
 
 use crate::common::tsafe::TSafe;
 use crate::actors::dispatcher::Dispatcher;
-use crate::actors::mailbox::Mailbox;
+use crate::actors::mailbox::{Mailbox, MailboxGate};
 use crate::actors::actor_context::ActorContext;
-use crate::actors::actor::Actor;
+use crate::actors::actor::{Actor, Behavior};
 use crate::actors::actor_path::ActorPath;
 use crate::actors::abstract_actor_system::AbstractActorSystem;
 use crate::actors::envelope::Envelope;
 use crate::actors::abstract_actor_ref::AbstractActorRef;
 use crate::actors::abstract_actor_ref::ActorRef;
 use crate::actors::local_actor_ref::LocalActorRef;
-use crate::actors::watcher::WatchingEvents;
+use crate::actors::calling_thread_dispatcher::CallingThreadDispatcher;
+use crate::actors::watcher::{WatchingEvents, StopReason};
 use crate::actors::message::Message;
 use crate::actors::error::Error;
-use crate::actors::supervision::SupervisionStrategy;
-use std::collections::HashMap;
+use crate::actors::supervision::{SupervisionStrategy, SupervisorStrategy, RestartLimit, SiblingFailed};
+use crate::actors::props::ActorFactory;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use std::any::Any;
 
 pub struct ActorCell {
@@ -38,6 +42,9 @@ pub struct ActorCell {
     /// Object which extends the actor trait and contain application logic
     pub actor: TSafe<Actor + Send>,
 
+    /// Constructor of the actor object. If exists, actor object is recreated at restart.
+    pub factory: Option<ActorFactory>,
+
     ///  Actor path object that represents the actor position in the actors hierarchy
     pub path: TSafe<ActorPath>,
 
@@ -57,7 +64,21 @@ pub struct ActorCell {
     pub childs: HashMap<String, TSafe<ActorCell>>,
 
     /// Actor's supervision strategy
-    pub supervision_strategy: SupervisionStrategy
+    pub supervision_strategy: SupervisionStrategy,
+
+    /// Strategy with which the actor supervises his childs. If it is not set, failed childs uses
+    /// their own supervision strategies.
+    pub supervisor_strategy: Option<TSafe<SupervisorStrategy>>,
+
+    /// Limit of restarts of the actor
+    pub restart_limit: Option<RestartLimit>,
+
+    /// Times of the last restarts performed by the supervision. Used for checking the restart limit.
+    pub restarts: VecDeque<Instant>,
+
+    /// Stack of behaviors installed through become_ calls. Top of the stack is used as message
+    /// handler instead of the actor's receive function. If stack is empty, receive is used.
+    pub behaviors: Vec<Behavior>
 }
 
 impl ActorCell {
@@ -73,8 +94,16 @@ impl ActorCell {
         parent: Option<TSafe<ActorCell>>,
         supervision_strategy: SupervisionStrategy) -> ActorCell {
 
+        // Dispatcher may require to use his own mailbox instead of the actor's mailbox
+        let shared_mailbox = dispatcher.lock().unwrap().shared_mailbox();
+        let mailbox = match shared_mailbox {
+            Some(mailbox) => mailbox,
+            None => mailbox
+        };
+
         ActorCell {
             actor,
+            factory: None,
             bid,
             dispatcher,
             mailbox,
@@ -84,67 +113,173 @@ impl ActorCell {
             stopped: true,
             parent,
             childs: HashMap::new(),
-            supervision_strategy
+            supervision_strategy,
+            supervisor_strategy: None,
+            restart_limit: None,
+            restarts: VecDeque::new(),
+            behaviors: Vec::new()
         }
     }
 
     /// Fail handler of actor. This method calls when message handler was completed with error.
-    /// Here code realize a supervision strategy of actor and decide what to do next.
-    pub fn fail(&mut self, err: Error, boxed_self: TSafe<ActorCell>) -> impl FnOnce() -> () {
+    /// Here code realize a supervision strategy of actor and decide what to do next. If the parent
+    /// has supervisor strategy, action is decided by it. Otherwise the actor's own supervision
+    /// strategy is used. Message is the message at which processing the error was occurred. It
+    /// does not exists if error was escalated from a child.
+    pub fn fail(&mut self, err: Error, msg: Option<Message>, boxed_self: TSafe<ActorCell>) -> impl FnOnce() -> () {
         //self.suspended = true;
 
         let system = self.system.clone();
         let path = self.path.clone();
         let supervision_strategy = self.supervision_strategy.clone();
+        let parent = self.parent.clone();
+        let actor = self.actor.clone();
 
         move || {
-            let self_: ActorRef =  Box::new(LocalActorRef::new(boxed_self.clone(), path));
+            let self_: ActorRef =  Box::new(LocalActorRef::new(boxed_self.clone(), path.clone()));
             let sender = system.lock().unwrap().dead_letters();
-            let system = system.clone();
 
-            let ctx = ActorContext::new(sender, self_.clone(), system, boxed_self.clone());
+            let ctx = ActorContext::new(sender, self_.clone(), system.clone(), boxed_self.clone());
 
-            match supervision_strategy {
-                SupervisionStrategy::Resume => {
-                    let actor = {
-                        let boxed_self = boxed_self.lock().unwrap();
-                        boxed_self.actor.clone()
-                    };
+            let supervisor = match &parent {
+                Some(parent) => parent.lock().unwrap().supervisor_strategy.clone(),
+                None => None
+            };
 
-                    actor.lock().unwrap().pre_fail(ctx, err, SupervisionStrategy::Resume);
+            let (decided, all_for_one) = match supervisor {
+                Some(supervisor) => {
+                    let supervisor = supervisor.lock().unwrap();
+                    (supervisor.decide(&err), supervisor.is_all_for_one())
                 },
-                SupervisionStrategy::Restart => {
-                    let actor = {
-                        let boxed_self = boxed_self.lock().unwrap();
-                        boxed_self.actor.clone()
-                    };
+                None => (supervision_strategy, false)
+            };
 
-                    actor.lock().unwrap().pre_fail(ctx, err, SupervisionStrategy::Restart);
-                    let f = boxed_self.lock().unwrap().restart(boxed_self.clone());
-                    f();
-                },
-                SupervisionStrategy::Stop => {
-                    let actor = {
-                        let boxed_self = boxed_self.lock().unwrap();
-                        boxed_self.actor.clone()
-                    };
+            // Restart may be replaced by other action, if the actor restarts too often
+            let strategy = if decided == SupervisionStrategy::Restart {
+                boxed_self.lock().unwrap().register_restart()
+            } else {
+                decided.clone()
+            };
 
-                    actor.lock().unwrap().pre_fail(ctx, err, SupervisionStrategy::Stop);
-                    let f = boxed_self.lock().unwrap().stop(boxed_self.clone());
-                    f();
-                },
-                SupervisionStrategy::Escalate => {
-                    let (actor, parent) = {
-                        let boxed_self = boxed_self.lock().unwrap();
-                        (boxed_self.actor.clone(), boxed_self.parent.clone())
+            actor.lock().unwrap().pre_fail(ctx, err.clone(), strategy.clone());
+            {
+                let _hold = CallingThreadDispatcher::hold();
+                system.lock().unwrap().register_watch_event(&self_, WatchingEvents::Failed(err.clone(), strategy.clone()));
+            }
+
+            // Siblings may process messages at this moment, so they are suspended first, and the
+            // decided action is applied to them by they own dispatchers, after they process
+            // messages which already is in their mailboxes
+            if all_for_one && (decided == SupervisionStrategy::Restart || decided == SupervisionStrategy::Stop) {
+                let siblings: Vec<TSafe<ActorCell>> = {
+                    let parent = parent.as_ref().unwrap().lock().unwrap();
+                    parent.childs.values()
+                        .filter(|c| !Arc::ptr_eq(c, &boxed_self))
+                        .map(|c| c.clone())
+                        .collect()
+                };
+
+                for sibling in siblings {
+                    let mut sibling_u = sibling.lock().unwrap();
+                    if sibling_u.stopped {
+                        continue;
+                    }
+                    sibling_u.suspend();
+                    let to_ref = Box::new(LocalActorRef::new(sibling.clone(), sibling_u.path.clone()));
+                    let command = SiblingFailed {
+                        strategy: decided.clone(),
+                        err: err.clone()
                     };
+                    sibling_u.force_send(sibling.clone(), msg!(command), None, to_ref);
+                }
+            }
+
+            ActorCell::apply_strategy(&boxed_self, strategy, err, msg);
+        }
+    }
 
-                    actor.lock().unwrap().pre_fail(ctx, err.clone(), SupervisionStrategy::Escalate);
-                    let parent = parent.unwrap();
-                    let f = parent.lock().unwrap().fail(err, parent.clone());
-                    f();
+    /// Applies supervision action to the sibling of the actor failed under all-for-one strategy.
+    /// Called by the dispatcher, when the sibling processed all messages which was in his mailbox
+    /// before his suspension. Restart limit of the sibling is checked as for the failed actor.
+    pub fn supervise_sibling(boxed_self: &TSafe<ActorCell>, strategy: SupervisionStrategy, err: Error) {
+        let strategy = {
+            let mut cell = boxed_self.lock().unwrap();
+            if cell.stopped {
+                return;
+            }
+
+            if strategy == SupervisionStrategy::Restart {
+                cell.register_restart()
+            } else {
+                strategy
+            }
+        };
+
+        ActorCell::apply_strategy(boxed_self, strategy, err, None);
+    }
+
+    /// Applies supervision action to the cell. Suspended cell receives messages again after the
+    /// restart or the resume. Escalate fails the parent, or stops the cell if it has no parent.
+    fn apply_strategy(boxed_self: &TSafe<ActorCell>, strategy: SupervisionStrategy, err: Error, msg: Option<Message>) {
+        let parent = boxed_self.lock().unwrap().parent.clone();
+
+        match strategy {
+            SupervisionStrategy::Resume => {
+                boxed_self.lock().unwrap().suspended = false;
+            },
+            SupervisionStrategy::Restart => {
+                let f = boxed_self.lock().unwrap().restart(err, msg, boxed_self.clone());
+                f();
+                boxed_self.lock().unwrap().suspended = false;
+            },
+            SupervisionStrategy::Stop => {
+                ActorCell::stop_by_supervision(boxed_self, &parent);
+            },
+            SupervisionStrategy::Escalate => {
+                match parent {
+                    Some(parent) => {
+                        let f = parent.lock().unwrap().fail(err, None, parent.clone());
+                        f();
+                    },
+                    None => {
+                        warn!("Actor {} has no parent for escalation of the failure, so he will be stopped", boxed_self.lock().unwrap().path.lock().unwrap());
+                        ActorCell::stop_by_supervision(boxed_self, &None);
+                    }
                 }
-            };
+            }
+        };
+    }
+
+    /// Stops the cell with the supervision reason and removes it from the parent's childs
+    fn stop_by_supervision(boxed_self: &TSafe<ActorCell>, parent: &Option<TSafe<ActorCell>>) {
+        let f = boxed_self.lock().unwrap().stop_with_reason(boxed_self.clone(), Some(StopReason::Supervision));
+        f();
+
+        if let Some(parent) = parent {
+            let name = boxed_self.lock().unwrap().path.lock().unwrap().name.clone();
+            parent.lock().unwrap().childs.remove(&name);
+        }
+    }
+
+    /// Registers new restart of the actor and checks the restart limit. Returns Restart if limit
+    /// is not exceeded, or exceed strategy of the limit otherwise.
+    pub fn register_restart(self: &mut Self) -> SupervisionStrategy {
+        let limit = match &self.restart_limit {
+            Some(limit) => limit.clone(),
+            None => return SupervisionStrategy::Restart
+        };
+
+        let now = Instant::now();
+        while self.restarts.len() > 0 && now.duration_since(self.restarts[0]) > limit.within {
+            self.restarts.pop_front();
+        }
+        self.restarts.push_back(now);
+
+        if self.restarts.len() as u32 > limit.max_restarts {
+            self.restarts.clear();
+            limit.exceed_strategy
+        } else {
+            SupervisionStrategy::Restart
         }
     }
 
@@ -167,27 +302,50 @@ impl ActorCell {
             };
 
             actor.lock().unwrap().pre_start(ctx);
-            boxed_self.lock().unwrap().stopped = false;
+
+            let dispatcher = {
+                let mut boxed_self_u = boxed_self.lock().unwrap();
+                boxed_self_u.stopped = false;
+                boxed_self_u.dispatcher.clone()
+            };
+            dispatcher.lock().unwrap().attach(boxed_self.clone(), actor);
         }
     }
 
-    pub fn restart(self: &mut Self, boxed_self: TSafe<ActorCell>) -> impl FnOnce() -> ()  {
-        let self_ =  Box::new(LocalActorRef::new(boxed_self.clone(), self.path.clone()));
+    /// Restarts the actor. Calls pre_restart hook of the current actor object and stops him. If
+    /// the actor has factory, replaces the actor object by the new one. After that starts the
+    /// actor and calls post_restart hook.
+    pub fn restart(self: &mut Self, err: Error, msg: Option<Message>, boxed_self: TSafe<ActorCell>) -> impl FnOnce() -> ()  {
+        let self_: ActorRef =  Box::new(LocalActorRef::new(boxed_self.clone(), self.path.clone()));
         let sender = self.system.lock().unwrap().dead_letters();
         let system = self.system.clone();
 
-        let ctx = ActorContext::new(sender, self_, system, boxed_self.clone());
+        let pre_ctx = ActorContext::new(sender.clone(), self_.clone(), system.clone(), boxed_self.clone());
+        let ctx = ActorContext::new(sender, self_.clone(), system, boxed_self.clone());
 
         move || {
+            let actor = boxed_self.lock().unwrap().actor.clone();
+            actor.lock().unwrap().pre_restart(pre_ctx, err.clone(), msg);
+
             let f = {
                 let mut boxed_self_o = boxed_self.lock().unwrap();
-                let f = boxed_self_o.stop(boxed_self.clone());
+                let f = boxed_self_o.stop_with_reason(boxed_self.clone(), None);
                 f
             };
             f();
 
             let f = {
                 let mut boxed_self_o = boxed_self.lock().unwrap();
+                boxed_self_o.behaviors.clear();
+
+                let actor = match &boxed_self_o.factory {
+                    Some(factory) => Some(factory()),
+                    None => None
+                };
+                if let Some(actor) = actor {
+                    boxed_self_o.actor = actor;
+                }
+
                 let f = boxed_self_o.start(boxed_self.clone());
                 f
             };
@@ -197,41 +355,77 @@ impl ActorCell {
                 let boxed_self = boxed_self.lock().unwrap();
                 (boxed_self.actor.clone(), boxed_self.system.clone())
             };
-
             actor.lock().unwrap().post_restart(ctx);
+            {
+                let _hold = CallingThreadDispatcher::hold();
+                system.lock().unwrap().register_watch_event(&self_, WatchingEvents::Restarted(err));
+            }
         }
     }
 
     /// Stops the actor. Prohibits receiving new messages and calls the postStop hook.
     pub fn stop(self: &mut Self, boxed_self: TSafe<ActorCell>) -> impl FnOnce() -> () {
+        self.stop_with_reason(boxed_self, Some(StopReason::Normal))
+    }
+
+    /// Stops the actor with the specified reason, which is passed to the watchers with the
+    /// Terminated event. Reason is absent when the actor is stopped for restart. In this case
+    /// Terminated event is not registered.
+    pub fn stop_with_reason(self: &mut Self, boxed_self: TSafe<ActorCell>, reason: Option<StopReason>) -> impl FnOnce() -> () {
         self.stopped = true;
+        self.dispatcher.lock().unwrap().detach(&boxed_self);
 
         //FIXME this is potential memory leak place! What happen if an actor is stopped but his mailbox is not empty?
         //self.mailbox.lock().unwrap().clean_up();
 
-        // Stop all childs
-        for (path, cell) in self.childs.iter() {
-            let cell = cell.clone();
+        let self_: ActorRef =  Box::new(LocalActorRef::new(boxed_self.clone(), self.path.clone()));
+
+        // Childs is stopped together with the actor, so he does not need their Terminated events.
+        // They are only may be dropped to the deadLetter, but this requires lock of the actor
+        // system, which is shared with the childs and is held while the events is registered.
+        let sender = {
+            let mut system = self.system.lock().unwrap();
+            for (name, _) in self.childs.iter() {
+                let child_path = tsafe!(ActorPath::new(name, Some(self.path.clone())));
+                let child: ActorRef = Box::new(LocalActorRef::new(boxed_self.clone(), child_path));
+                system.unwatch(&self_, &child);
+            }
+            system.dead_letters()
+        };
+
+        // Stop all childs. Their postStop hooks and Terminated events is called after this cell will
+        // be released, because events may be sent to this actor.
+        let child_reason = match reason {
+            Some(StopReason::SystemShutdown) => StopReason::SystemShutdown,
+            _ => StopReason::Normal
+        };
+        let mut child_stops: Vec<Box<FnOnce()>> = Vec::new();
+        for (_, cell) in self.childs.iter() {
             let boxed_cell = cell.clone();
-            let  f = cell.lock().unwrap().stop(boxed_cell);
-            f();
+            let f = cell.lock().unwrap().stop_with_reason(boxed_cell, Some(child_reason.clone()));
+            child_stops.push(Box::new(f));
         }
         self.childs.clear();
 
-        let self_: ActorRef =  Box::new(LocalActorRef::new(boxed_self.clone(), self.path.clone()));
-        let sender = self.system.lock().unwrap().dead_letters();
         let system = self.system.clone();
 
         let ctx = ActorContext::new(sender, self_.clone(), system, boxed_self.clone());
 
         move || {
+            for f in child_stops {
+                f();
+            }
+
             let (actor, system) = {
                 let boxed_self = boxed_self.lock().unwrap();
                 (boxed_self.actor.clone(), boxed_self.system.clone())
             };
 
             actor.lock().unwrap().post_stop(ctx);
-            system.lock().unwrap().register_watch_event(&self_, WatchingEvents::Terminated);
+            if let Some(reason) = reason {
+                let _hold = CallingThreadDispatcher::hold();
+                system.lock().unwrap().register_watch_event(&self_, WatchingEvents::Terminated(reason));
+            }
 
         }
 
@@ -242,6 +436,12 @@ impl ActorCell {
         self.suspended = true;
     }
 
+    /// Returns behavior from the top of the behaviors stack. None means that the actor's receive
+    /// function must be used as message handler.
+    pub fn current_behavior(self: &Self) -> Option<Behavior> {
+        self.behaviors.last().map(|b| b.clone())
+    }
+
     /// Sends the message to the actor. Creates new envelope with the message and indicates to
     /// dispatcher to schedule execution of this envelope. Message sends to the actors may be done,
     /// only if flags suspended and stopped will be dropped. Otherwise, the message will be dropped
@@ -252,26 +452,46 @@ impl ActorCell {
                 rself: Option<ActorRef>,
                 to_ref: Box<AbstractActorRef + Send>) {
 
-        // If cell does not receive new messages, drops message to the deadLetter
-        if self.stopped || self.suspended {
-            let mut dead_letters = self.system.lock().unwrap().dead_letters();
-            dead_letters.cell().lock().unwrap().send(&dead_letters.cell(),
-                                                   msg, rself,
-                                                   to_ref);
-        } else {
-            let envelope = Envelope::new(
-                msg,
-                rself,
-                to_ref,
-                self.system.clone());
-
-
-            self.dispatcher.lock().unwrap().dispatch(
-                boxed_self.clone(),
-                self.bid,
-                self.mailbox.clone(),
-                self.actor.clone(), envelope);
+        let envelope = Envelope::new(
+            msg,
+            rself,
+            to_ref,
+            self.system.clone());
+
+        self.post(boxed_self, envelope, false);
+    }
+
+    /// Sends message through the cell and after the cell was released, processes messages which
+    /// was planned on the current thread by the calling thread dispatcher (if the current thread
+    /// does not hold the actor system, otherwise they are processed later). All actor references
+    /// must send messages through this function, otherwise messages of the actors with calling
+    /// thread dispatcher will stay in the queue of the current thread. If the mailbox is full and
+    /// blocks senders, caller waits for free space outside of the cell lock and tries again, until
+    /// the timeout of the mailbox will be reached. After that, message is dropped to the deadLetter.
+    pub fn send_and_run(boxed_self: &TSafe<ActorCell>,
+                        msg: Message,
+                        rself: Option<ActorRef>,
+                        to_ref: Box<AbstractActorRef + Send>) {
+        let mut blocked = {
+            let mut cell = boxed_self.lock().unwrap();
+            let envelope = Envelope::new(msg, rself, to_ref, cell.system.clone());
+            cell.post(boxed_self, envelope, true)
+        };
+
+        let deadline = blocked.as_ref().map(|(_, gate)| Instant::now() + gate.timeout());
+        while let Some((envelope, gate)) = blocked {
+            let free = gate.wait(deadline.unwrap());
+
+            let mut cell = boxed_self.lock().unwrap();
+            blocked = if free {
+                cell.post(boxed_self, envelope, true)
+            } else {
+                cell.drop_to_dead_letters(vec![envelope]);
+                None
+            };
         }
+
+        CallingThreadDispatcher::run_pending();
     }
 
     /// Performs action identical to the send method do, but with ignoring state of the stopping
@@ -288,11 +508,63 @@ impl ActorCell {
             to_ref,
             self.system.clone());
 
+        self.dispatch(boxed_self, envelope, false);
+    }
+
+    /// Sends envelope through the dispatcher, if the cell receives new messages. Otherwise drops
+    /// it to the deadLetter.
+    fn post(self: &mut Self, boxed_self: &TSafe<ActorCell>, envelope: Envelope, may_wait: bool) -> Option<(Envelope, MailboxGate)> {
+        if self.stopped || self.suspended {
+            self.drop_to_dead_letters(vec![envelope]);
+            None
+        } else {
+            self.dispatch(boxed_self.clone(), envelope, may_wait)
+        }
+    }
+
+    /// Sends envelope through the dispatcher. If the mailbox is full and blocks senders, envelope
+    /// is returned back with the gate of the mailbox, when the sender may wait on it. In other
+    /// cases such envelope is dropped to the deadLetter.
+    fn dispatch(self: &mut Self, boxed_self: TSafe<ActorCell>, envelope: Envelope, may_wait: bool) -> Option<(Envelope, MailboxGate)> {
         self.dispatcher.lock().unwrap().dispatch(
             boxed_self,
             self.bid,
             self.mailbox.clone(),
             self.actor.clone(), envelope);
+
+        self.forward_rejected();
+
+        let (mut blocked, gate) = {
+            let mut mailbox = self.mailbox.lock().unwrap();
+            (mailbox.drain_blocked(), mailbox.gate())
+        };
+
+        if may_wait && blocked.len() == 1 && gate.is_some() {
+            return Some((blocked.pop().unwrap(), gate.unwrap()));
+        }
+
+        self.drop_to_dead_letters(blocked);
+        None
+    }
+
+    /// Drops messages rejected by the mailbox to the deadLetter. This is performed after the
+    /// dispatcher was released, because deadLetter is served by the same dispatcher.
+    fn forward_rejected(self: &mut Self) {
+        let rejected = self.mailbox.lock().unwrap().drain_rejected();
+        self.drop_to_dead_letters(rejected);
+    }
+
+    /// Sends envelopes to the deadLetter with their original senders and receivers
+    fn drop_to_dead_letters(self: &mut Self, envelopes: Vec<Envelope>) {
+        if envelopes.len() > 0 {
+            let mut dead_letters = self.system.lock().unwrap().dead_letters();
+            for envelope in envelopes {
+                dead_letters.cell().lock().unwrap().send(&dead_letters.cell(),
+                                                         envelope.message,
+                                                         envelope.sender,
+                                                         envelope.receiver);
+            }
+        }
     }
 }
 
@@ -305,4 +577,226 @@ impl ActorCell {
 
 
 // Attentions!!! This object does't do be cloned. Cloned must by on the boxed (TSave) value of the
-// cell.
\ No newline at end of file
+// cell.
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    /// Records processed messages and lifecycle events. Fails on the "fail" message.
+    struct Child {
+        log: Arc<Mutex<Vec<String>>>
+    }
+
+    impl Actor for Child {
+        fn post_stop(&mut self, _ctx: ActorContext) {
+            self.log.lock().unwrap().push(String::from("stopped"));
+        }
+
+        fn post_restart(&mut self, _ctx: ActorContext) {
+            self.log.lock().unwrap().push(String::from("restarted"));
+        }
+
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<&str>() {
+                Some(&"fail") => panic!("Child failure"),
+                Some(&"slow") => {
+                    thread::sleep(Duration::from_millis(50));
+                    self.log.lock().unwrap().push(String::from("slow"));
+                },
+                _ => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    /// Creates two childs with own logs and stores their refs
+    struct Parent {
+        childs: Arc<Mutex<Vec<ActorRef>>>,
+        logs: Vec<Arc<Mutex<Vec<String>>>>,
+        limit: Option<RestartLimit>
+    }
+
+    impl Actor for Parent {
+        fn pre_start(&mut self, mut ctx: ActorContext) {
+            for log in self.logs.iter() {
+                let mut props = Props::new(tsafe!(Child { log: log.clone() }));
+                if let Some(limit) = self.limit.as_ref() {
+                    props = props.with_restart_limit(limit.clone());
+                }
+                let child = ctx.actor_of(props, None);
+                self.childs.lock().unwrap().push(child);
+            }
+        }
+
+        fn receive(&mut self, _msg: Message, _ctx: ActorContext) -> HandleResult {
+            Ok(false)
+        }
+    }
+
+    /// Counts received numbers and records restart hooks. Fails on the "fail" message.
+    struct Counter {
+        count: u32,
+        log: Arc<Mutex<Vec<String>>>
+    }
+
+    impl Actor for Counter {
+        fn pre_restart(&mut self, _ctx: ActorContext, _err: Error, msg: Option<Message>) {
+            let msg = msg.map(|m| m.get().downcast_ref::<&str>().map(|s| String::from(*s)));
+            self.log.lock().unwrap().push(format!("pre_restart {:?}", msg));
+        }
+
+        fn post_restart(&mut self, _ctx: ActorContext) {
+            self.log.lock().unwrap().push(String::from("post_restart"));
+        }
+
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            let msg = msg.get();
+            if let Some(&"fail") = msg.downcast_ref::<&str>() {
+                return Err(err!("fail"));
+            }
+            match msg.downcast_ref::<u32>() {
+                Some(_) => {
+                    self.count = self.count + 1;
+                    self.log.lock().unwrap().push(format!("count {}", self.count));
+                },
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    /// Counts twice, fails and counts again
+    fn count_with_restart(props: Props, log: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+        let mut system = LocalActorSystem::new();
+        let mut counter = system.actor_of(props, None);
+
+        counter.tell(msg!(1 as u32), None);
+        counter.tell(msg!(1 as u32), None);
+        counter.tell(msg!("fail"), None);
+        counter.tell(msg!(1 as u32), None);
+        wait_log(log, 5);
+        thread::sleep(Duration::from_millis(50));
+
+        system.terminate();
+        let log = log.lock().unwrap().clone();
+        log
+    }
+
+    fn new_log() -> Arc<Mutex<Vec<String>>> {
+        Arc::new(Mutex::new(Vec::new()))
+    }
+
+    fn wait_log(log: &Arc<Mutex<Vec<String>>>, len: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < len && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn all_for_one(system: &mut LocalActorSystem, logs: Vec<Arc<Mutex<Vec<String>>>>, limit: Option<RestartLimit>) -> Vec<ActorRef> {
+        let childs = Arc::new(Mutex::new(Vec::new()));
+        let count = logs.len();
+        let parent = Parent { childs: childs.clone(), logs, limit };
+        let props = Props::new(tsafe!(parent))
+            .with_supervisor_strategy(SupervisorStrategy::all_for_one(|_err| SupervisionStrategy::Restart));
+        system.actor_of(props, None);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while childs.lock().unwrap().len() < count && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let childs = childs.lock().unwrap();
+        childs.iter().map(|c| (*c).clone()).collect()
+    }
+
+    #[test]
+    fn escalate_without_parent_stops_actor() {
+        let mut system = LocalActorSystem::new();
+        let log = new_log();
+        let props = Props::new(tsafe!(Child { log: log.clone() }))
+            .with_supervision_strategy(SupervisionStrategy::Escalate);
+        let mut actor = system.actor_of(props, None);
+        actor.cell().lock().unwrap().parent = None;
+
+        actor.tell(msg!("fail"), None);
+        wait_log(&log, 1);
+
+        assert_eq!(*log.lock().unwrap(), vec!["stopped"]);
+        assert!(actor.cell().lock().unwrap().stopped);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn all_for_one_restarts_siblings_after_their_queued_messages() {
+        let mut system = LocalActorSystem::new();
+        let failed_log = new_log();
+        let sibling_log = new_log();
+        let mut childs = all_for_one(&mut system, vec![failed_log.clone(), sibling_log.clone()], None);
+
+        for _ in 0..3 {
+            childs[1].tell(msg!("slow"), None);
+        }
+        childs[0].tell(msg!("fail"), None);
+
+        wait_log(&sibling_log, 5);
+        assert_eq!(*sibling_log.lock().unwrap(), vec!["slow", "slow", "slow", "stopped", "restarted"]);
+        assert_eq!(*failed_log.lock().unwrap(), vec!["stopped", "restarted"]);
+
+        // Sibling receives messages again after the restart
+        childs[1].tell(msg!("slow"), None);
+        wait_log(&sibling_log, 6);
+        assert_eq!(sibling_log.lock().unwrap().len(), 6);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn restart_limit_is_checked_for_each_restarted_sibling() {
+        let mut system = LocalActorSystem::new();
+        let failed_log = new_log();
+        let sibling_log = new_log();
+        let limit = RestartLimit::new(1, Duration::from_secs(10));
+        let mut childs = all_for_one(&mut system, vec![failed_log.clone(), sibling_log.clone()], Some(limit));
+
+        childs[0].tell(msg!("fail"), None);
+        wait_log(&sibling_log, 2);
+        assert_eq!(*sibling_log.lock().unwrap(), vec!["stopped", "restarted"]);
+
+        // Second restart within the window exceeds the limit of each child, so both is stopped
+        childs[0].tell(msg!("fail"), None);
+        wait_log(&sibling_log, 3);
+        wait_log(&failed_log, 3);
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(*sibling_log.lock().unwrap(), vec!["stopped", "restarted", "stopped"]);
+        assert_eq!(*failed_log.lock().unwrap(), vec!["stopped", "restarted", "stopped"]);
+        assert!(childs[1].cell().lock().unwrap().stopped);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn actor_state_is_kept_at_restart_with_props_new() {
+        let log = new_log();
+        let props = Props::new(tsafe!(Counter { count: 0, log: log.clone() }));
+
+        let log = count_with_restart(props, &log);
+        assert_eq!(log, vec!["count 1", "count 2", "pre_restart Some(Some(\"fail\"))", "post_restart", "count 3"]);
+    }
+
+    #[test]
+    fn actor_state_is_reset_at_restart_with_props_from_factory() {
+        let log = new_log();
+        let log_c = log.clone();
+        let props = Props::from_factory(move || tsafe!(Counter { count: 0, log: log_c.clone() }));
+
+        // pre_restart receives the failed message before post_restart
+        let log = count_with_restart(props, &log);
+        assert_eq!(log, vec!["count 1", "count 2", "pre_restart Some(Some(\"fail\"))", "post_restart", "count 1"]);
+    }
+}
@@ -4,16 +4,20 @@
 use crate::actors::abstract_actor_system::AbstractActorSystem;
 use crate::actors::actor_ref_factory::{ActorRefFactory, ActorSelectError};
 use crate::actors::abstract_actor_ref::ActorRef;
-use crate::actors::unbound_mailbox::UnboundMailbox;
+use crate::actors::error::Error;
 use crate::actors::actor_path::ActorPath;
 use crate::actors::local_actor_ref::LocalActorRef;
 use crate::actors::actor_cell::ActorCell;
 use crate::actors::pinned_dispatcher::PinnedDispatcher;
 use crate::actors::props::Props;
+use crate::actors::actor::HandleResult;
+use crate::actors::message::Message;
 use crate::common::tsafe::TSafe;
 use crate::actors::dispatcher::Dispatcher;
 use crate::futures::future::{Future, WrappedFuture};
+use crate::actors::calling_thread_dispatcher::{CallingThreadDispatcher, PendingHold};
 use std::sync::{Mutex, Arc, MutexGuard};
+use std::ops::{Deref, DerefMut};
 
 //pub struct WrappedCell {
 //    refed: Option<&ActorCell>,
@@ -49,14 +53,71 @@ impl ActorContext {
         }
     }
 
-    pub fn system(&self) -> MutexGuard<AbstractActorSystem + Send + 'static> {
-        self.system.lock().unwrap()
+    /// Locks the actor system. Messages of the actors with calling thread dispatcher, which is sent
+    /// while the lock is held, is processed after the lock will be released.
+    pub fn system(&self) -> SystemGuard {
+        let hold = CallingThreadDispatcher::hold();
+        SystemGuard {
+            guard: self.system.lock().unwrap(),
+            _hold: hold
+        }
+    }
+
+    /// Replaces current message handler of the actor with the specified behavior. If the
+    /// behaviors stack is empty, behavior will be pushed to it. New handler will be used starting
+    /// from the next message. Behavior is a free closure without access to the actor object, so
+    /// state which is used by both of them must be shared through TSafe.
+    pub fn become_<F>(&mut self, f: F)
+        where F: 'static + FnMut(Message, ActorContext) -> HandleResult + Send
+    {
+        let mut cell = self.cell.lock().unwrap();
+        cell.behaviors.pop();
+        cell.behaviors.push(tsafe!(f));
+    }
+
+    /// Pushes the specified behavior to the top of the behaviors stack. Previous handler will be
+    /// restored after unbecome call.
+    pub fn become_stacked<F>(&mut self, f: F)
+        where F: 'static + FnMut(Message, ActorContext) -> HandleResult + Send
+    {
+        self.cell.lock().unwrap().behaviors.push(tsafe!(f));
+    }
+
+    /// Pops current behavior from the behaviors stack. If stack becomes empty, the actor's
+    /// receive function will be used as message handler.
+    pub fn unbecome(&mut self) {
+        self.cell.lock().unwrap().behaviors.pop();
+    }
+}
+
+/// Locked actor system. Fields is dropped in the order of declaration, so the lock is released
+/// before the hold.
+pub struct SystemGuard<'a> {
+    guard: MutexGuard<'a, AbstractActorSystem + Send + 'static>,
+    _hold: PendingHold
+}
+
+impl<'a> Deref for SystemGuard<'a> {
+    type Target = AbstractActorSystem + Send + 'static;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.guard
+    }
+}
+
+impl<'a> DerefMut for SystemGuard<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *self.guard
     }
 }
 
 impl ActorRefFactory for ActorContext {
     fn actor_of(self: &mut Self, props: Props, name: Option<&str>) -> ActorRef {
-        let mailbox = tsafe!(UnboundMailbox::new());
+        let mailbox = {
+            let mailboxes = self.system().get_mailboxes();
+            let mailboxes = mailboxes.lock().unwrap();
+            props.mailbox.create(&mailboxes)
+        };
 
         let mut aname: String;
 
@@ -92,7 +153,7 @@ impl ActorRefFactory for ActorContext {
         };
 
 
-        let cell = ActorCell::new(
+        let mut cell = ActorCell::new(
             self.system.clone(),
             path.clone(),
             props.actor,
@@ -101,6 +162,9 @@ impl ActorRefFactory for ActorContext {
             mailbox,
             Some(self.cell.clone()),
             props.supervision_strategy);
+        cell.supervisor_strategy = props.supervisor_strategy;
+        cell.restart_limit = props.restart_limit;
+        cell.factory = props.factory;
         let boxed_cell = tsafe!(cell);
 
         {
@@ -142,7 +206,97 @@ impl ActorRefFactory for ActorContext {
         self.system().watch(watcher, observed)
     }
 
+    fn watch_lifecycle(&mut self, watcher: &ActorRef, observed: &ActorRef) -> Result<(), Error> {
+        self.system().watch_lifecycle(watcher, observed)
+    }
+
     fn unwatch(&mut self, watcher: &ActorRef, observed: &ActorRef) {
         self.system().unwatch(watcher, observed)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    /// Records by which handler each message was processed
+    struct Switcher {
+        log: Arc<Mutex<Vec<String>>>
+    }
+
+    impl Actor for Switcher {
+        fn receive(&mut self, msg: Message, mut ctx: ActorContext) -> HandleResult {
+            let text = *msg.get().downcast_ref::<&str>().unwrap();
+            self.log.lock().unwrap().push(format!("receive:{}", text));
+
+            match text {
+                "become" => {
+                    let log = self.log.clone();
+                    ctx.become_(move |msg, mut ctx| {
+                        let text = *msg.get().downcast_ref::<&str>().unwrap();
+                        log.lock().unwrap().push(format!("first:{}", text));
+                        match text {
+                            "stack" => {
+                                let log = log.clone();
+                                ctx.become_stacked(move |msg, mut ctx| {
+                                    let text = *msg.get().downcast_ref::<&str>().unwrap();
+                                    log.lock().unwrap().push(format!("second:{}", text));
+                                    if text == "back" {
+                                        ctx.unbecome();
+                                    }
+                                    Ok(true)
+                                });
+                            },
+                            "fail" => panic!("Behavior failure"),
+                            _ => ()
+                        }
+                        Ok(true)
+                    });
+                },
+                _ => ()
+            }
+            Ok(true)
+        }
+    }
+
+    fn run(messages: Vec<&'static str>) -> Vec<String> {
+        let mut system = LocalActorSystem::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut actor = system.actor_of(Props::new(tsafe!(Switcher { log: log.clone() })), None);
+
+        for msg in messages.iter() {
+            actor.tell(msg!(*msg), None);
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < messages.len() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(Duration::from_millis(50));
+
+        system.terminate();
+        let log = log.lock().unwrap().clone();
+        log
+    }
+
+    #[test]
+    fn become_replaces_receive_function() {
+        let log = run(vec!["a", "become", "b", "c"]);
+        assert_eq!(log, vec!["receive:a", "receive:become", "first:b", "first:c"]);
+    }
+
+    #[test]
+    fn unbecome_restores_previous_stacked_behavior() {
+        let log = run(vec!["become", "stack", "a", "back", "b"]);
+        assert_eq!(log, vec!["receive:become", "first:stack", "second:a", "second:back", "first:b"]);
+    }
+
+    #[test]
+    fn behaviors_stack_is_cleared_on_restart() {
+        let log = run(vec!["become", "stack", "back", "fail", "a"]);
+        assert_eq!(log, vec!["receive:become", "first:stack", "second:back", "first:fail", "receive:a"]);
+    }
+}
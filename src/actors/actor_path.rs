@@ -49,13 +49,16 @@ impl fmt::Display for ActorPath {
 
 impl PartialEq for ActorPath {
     fn eq(&self, other: &Self) -> bool {
-        if self.parent.is_some() {
-            let p1 = self.parent.as_ref().unwrap().lock().unwrap().to_string();
-            let p2 = other.parent.as_ref().unwrap().lock().unwrap().to_string();
-            let p_eq = p1 == p2;
-            p_eq  == true && self.name == other.name
-        } else {
-            self.name == other.name
+        match (&self.parent, &other.parent) {
+            (Some(p1), Some(p2)) => {
+                let p1 = p1.lock().unwrap().to_string();
+                let p2 = p2.lock().unwrap().to_string();
+                p1 == p2 && self.name == other.name
+            },
+            (None, None) => self.name == other.name,
+
+            // Root path is never equal to path of some child
+            _ => false
         }
 
 
@@ -1,8 +1,11 @@
 use crate::actors::props::Props;
 use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::error::Error;
+use crate::actors::watcher::LifecycleWatchError;
 use crate::futures::future::WrappedFuture;
 use crate::common::tsafe::TSafe;
 use std::any::Any;
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
 pub struct ActorSelectError {
@@ -19,6 +22,15 @@ pub trait ActorRefFactory {
     /// Register watcher for receive 'watching events' from observed actor
     fn watch(&mut self, watcher: &ActorRef, observed: &ActorRef);
 
+    /// Register watcher for receive 'watching events' from observed actor, including Restarted and
+    /// Failed events. Returns LifecycleWatchError if the system is unable to deliver this events.
+    /// By default factory does not support it.
+    fn watch_lifecycle(&mut self, _watcher: &ActorRef, observed: &ActorRef) -> Result<(), Error> {
+        Err(err!(LifecycleWatchError {
+            description: format!("Lifecycle of the actor {} can not be watched through this factory", observed)
+        }))
+    }
+
     /// Unregister watcher from receive 'watching events' from observed actor
     fn unwatch(&mut self, watcher: &ActorRef, observed: &ActorRef);
 }
\ No newline at end of file
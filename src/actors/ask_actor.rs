@@ -73,14 +73,16 @@ impl Actor for AskActor {
         if tp == 0 {
             self.p.failure(AskTimeoutError {});
             self.timers.as_ref().unwrap().lock().unwrap().cancel_all();
-            ctx.system.lock().unwrap().stop(&mut ctx.self_);
+            let mut self_ = ctx.self_.clone();
+            ctx.system().stop(&mut self_);
             Ok(true)
         } else if tp == 1 {
             Ok(false)
         } else {
             self.p.success(msg.clone());
             self.timers.as_ref().unwrap().lock().unwrap().cancel_all();
-            ctx.system.lock().unwrap().stop(&mut ctx.self_);
+            let mut self_ = ctx.self_.clone();
+            ctx.system().stop(&mut self_);
             Ok(true)
         }
     }
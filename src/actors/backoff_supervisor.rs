@@ -0,0 +1,431 @@
+//! Backoff supervisor
+//!
+//! Actor which creates a child and restarts him after he was failed or stopped. Restart is
+//! performed not immediately, but after the delay, which grows exponentially with each next
+//! restart, from the min to the max backoff. For avoid of simultaneous restarts of many actors,
+//! delay is extended on random part of him, specified by the random factor. If child was worked
+//! without termination for the reset time, delay is returned to the min backoff.
+//!
+//! All messages sent to the supervisor is forwarded to the child with preserving of the original
+//! sender. While child does not exists, messages is placed to the buffer, which is sent to the child
+//! after his restart. If buffer is overflowed, or it's size is zero, messages is dropped to the
+//! dead letters.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let props = BackoffSupervisor::new(Duration::from_millis(100), Duration::from_secs(30))
+//!     .set_random_factor(0.2)
+//!     .set_buffer_size(100)
+//!     .props("storage", || Props::new(tsafe!(Storage::new())));
+//!
+//! let mut supervisor = system.actor_of(props, Some("storage_supervisor"));
+//! supervisor.tell(msg!(storage::commands::Write { data }), None);
+//! ```
+
+use crate::actors::actor::{Actor, HandleResult, PoisonPill};
+use crate::actors::actor_context::ActorContext;
+use crate::actors::actor_ref_factory::ActorRefFactory;
+use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::message::Message;
+use crate::actors::props::Props;
+use crate::actors::scheduler::TaskGuard;
+use crate::actors::supervision::SupervisionStrategy;
+use crate::actors::watcher::events::Terminated;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex};
+use rand::Rng;
+
+/// Constructor of the child props. Called each time when supervisor creates new child.
+pub type ChildFactory = Box<Fn() -> Props + Send>;
+
+/// Message which initiates creation of the child
+struct StartChild {}
+
+pub struct BackoffSupervisor {
+
+    /// Delay before the first restart
+    min_backoff: Duration,
+
+    /// Maximum delay between restarts
+    max_backoff: Duration,
+
+    /// Random part of the delay
+    random_factor: f64,
+
+    /// Time of the child work, after which delay is reset to the min backoff
+    reset_after: Duration,
+
+    /// Maximum count of messages which may be buffered while child does not exists
+    buffer_size: usize,
+
+    /// Name of the child
+    child_name: String,
+
+    /// Constructor of the child
+    factory: Option<ChildFactory>,
+
+    /// Current child
+    child: Option<ActorRef>,
+
+    /// Time when current child was started
+    started_at: Instant,
+
+    /// Count of restarts after the last reset
+    restarts: u32,
+
+    /// Messages received while child does not exists
+    buffer: VecDeque<(Message, ActorRef)>,
+
+    /// Planned restart of the child
+    restart_guard: Option<TaskGuard>
+}
+
+impl BackoffSupervisor {
+
+    /// Creates supervisor with specified backoff bounds. Random factor = 0.2, reset time = max
+    /// backoff, buffer size = 0.
+    pub fn new(min_backoff: Duration, max_backoff: Duration) -> BackoffSupervisor {
+        if min_backoff > max_backoff {
+            panic!("Min backoff of the supervisor is greater than max backoff");
+        }
+
+        BackoffSupervisor {
+            min_backoff,
+            max_backoff,
+            random_factor: 0.2,
+            reset_after: max_backoff,
+            buffer_size: 0,
+            child_name: String::new(),
+            factory: None,
+            child: None,
+            started_at: Instant::now(),
+            restarts: 0,
+            buffer: VecDeque::new(),
+            restart_guard: None
+        }
+    }
+
+    /// Sets random part of the delay. For example 0.2 means that delay may be extended on 0-20%.
+    pub fn set_random_factor(mut self, factor: f64) -> BackoffSupervisor {
+        self.random_factor = factor;
+        self
+    }
+
+    /// Sets time of the child work, after which delay is reset to the min backoff
+    pub fn set_reset_after(mut self, reset_after: Duration) -> BackoffSupervisor {
+        self.reset_after = reset_after;
+        self
+    }
+
+    /// Sets maximum count of messages which may be buffered while child does not exists
+    pub fn set_buffer_size(mut self, size: usize) -> BackoffSupervisor {
+        self.buffer_size = size;
+        self
+    }
+
+    /// Creates props of the supervisor. Factory is called each time when child is created. Failed
+    /// child is always stopped, independently of his supervision strategy, and restarted by the
+    /// supervisor after the delay.
+    pub fn props<F>(mut self, child_name: &str, factory: F) -> Props
+        where F: Fn() -> Props + Send + 'static {
+        self.child_name = String::from(child_name);
+        self.factory = Some(Box::new(factory));
+        Props::new(tsafe!(self))
+    }
+
+    /// Returns delay before the next restart
+    fn next_delay(&self) -> Duration {
+        let mut delay = self.min_backoff;
+        for _ in 0..self.restarts {
+            delay = delay * 2;
+            if delay >= self.max_backoff {
+                delay = self.max_backoff;
+                break;
+            }
+        }
+
+        let jitter = rand::thread_rng().gen_range(0.0, 1.0) * self.random_factor;
+        let delay = delay.as_secs() as f64 + delay.subsec_nanos() as f64 / 1_000_000_000.0;
+        let delay = delay * (1.0 + jitter);
+        Duration::new(delay as u64, ((delay - delay.floor()) * 1_000_000_000.0) as u32)
+    }
+
+    /// Creates new child and sends him all buffered messages
+    fn start_child(&mut self, ctx: &mut ActorContext) {
+        let props = (self.factory.as_ref().unwrap())()
+            .with_supervision_strategy(SupervisionStrategy::Stop);
+
+        let self_ = ctx.self_.clone();
+        let mut child = ctx.actor_of(props, Some(&self.child_name));
+        ctx.system().watch(&self_, &child);
+        self.started_at = Instant::now();
+
+        while let Some((msg, sender)) = self.buffer.pop_front() {
+            child.tell(msg, Some(&sender));
+        }
+
+        self.child = Some(child);
+    }
+}
+
+impl Actor for BackoffSupervisor {
+
+    fn pre_start(&mut self, mut ctx: ActorContext) {
+        self.restarts = 0;
+        self.start_child(&mut ctx);
+    }
+
+    fn post_stop(&mut self, ctx: ActorContext) {
+        self.restart_guard = None;
+
+        if let Some(child) = self.child.take() {
+            ctx.system().unwatch(&ctx.self_, &child);
+        }
+
+        let mut dead_letters = ctx.system().dead_letters();
+        while let Some((msg, sender)) = self.buffer.pop_front() {
+            dead_letters.tell(msg, Some(&sender));
+        }
+    }
+
+    fn receive(&mut self, msg: Message, mut ctx: ActorContext) -> HandleResult {
+
+        // Child is stopped together with the supervisor
+        if msg.get().is::<PoisonPill>() {
+            return Ok(false);
+        }
+
+        if msg.get().is::<StartChild>() {
+            self.restart_guard = None;
+            if self.child.is_none() {
+                self.start_child(&mut ctx);
+            }
+            return Ok(true);
+        }
+
+        if msg.get().is::<Terminated>() {
+            let path = ctx.sender.path();
+            let is_child = match self.child.as_ref() {
+                Some(child) => child.path() == path,
+                None => false
+            };
+
+            if is_child {
+                self.child = None;
+
+                // Child may be stopped not through the supervision, so his cell may be still
+                // registered in the childs. It must be removed for the name may be reused.
+                ctx.cell.lock().unwrap().childs.remove(&self.child_name);
+
+                if self.started_at.elapsed() >= self.reset_after {
+                    self.restarts = 0;
+                }
+
+                let delay = self.next_delay();
+                self.restarts = self.restarts + 1;
+                debug!("Backoff supervisor {} will restart child in {:?}", ctx.self_, delay);
+
+                let mut self_ = ctx.self_.clone();
+                let scheduler = ctx.system().get_scheduler();
+                let guard = scheduler.lock().unwrap().schedule_once(delay, move || {
+                    self_.tell(msg!(StartChild {}), None);
+                });
+                self.restart_guard = Some(guard);
+            }
+
+            return Ok(true);
+        }
+
+        match self.child.as_mut() {
+            Some(child) => child.tell(msg, Some(&ctx.sender)),
+            None => {
+                if self.buffer.len() < self.buffer_size {
+                    self.buffer.push_back((msg, ctx.sender.clone()));
+                } else {
+                    ctx.system().dead_letters().tell(msg, Some(&ctx.sender));
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::backoff_supervisor::BackoffSupervisor;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    /// Fails on string message, records others
+    struct Fragile {
+        starts: Arc<Mutex<Vec<Instant>>>,
+        received: Arc<Mutex<Vec<u32>>>
+    }
+
+    impl Actor for Fragile {
+        fn pre_start(&mut self, _ctx: ActorContext) {
+            self.starts.lock().unwrap().push(Instant::now());
+        }
+
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            let msg = msg.get();
+            if msg.is::<String>() {
+                return Err(err!("fail"));
+            }
+            match msg.downcast_ref::<u32>() {
+                Some(v) => self.received.lock().unwrap().push(*v),
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    struct Listener {
+        dead: Arc<Mutex<Vec<u32>>>
+    }
+
+    impl Actor for Listener {
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<DeadLetter>() {
+                Some(dead_letter) => {
+                    if let Some(v) = dead_letter.message.get().downcast_ref::<u32>() {
+                        self.dead.lock().unwrap().push(*v);
+                    }
+                },
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    struct Setup {
+        system: LocalActorSystem,
+        supervisor: ActorRef,
+        starts: Arc<Mutex<Vec<Instant>>>,
+        received: Arc<Mutex<Vec<u32>>>
+    }
+
+    fn setup(supervisor: BackoffSupervisor) -> Setup {
+        let mut system = LocalActorSystem::new();
+        let starts = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let (starts_c, received_c) = (starts.clone(), received.clone());
+        let props = supervisor.props("child", move || {
+            Props::new(tsafe!(Fragile { starts: starts_c.clone(), received: received_c.clone() }))
+        });
+        let supervisor = system.actor_of(props, Some("supervisor"));
+
+        let setup = Setup { system, supervisor, starts, received };
+        wait_len(&setup.starts, 1);
+        setup
+    }
+
+    fn wait_len<T>(log: &Arc<Mutex<Vec<T>>>, len: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < len && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Fails the child and returns the delay after which he was started again
+    fn fail_and_measure(s: &mut Setup) -> Duration {
+        let count = s.starts.lock().unwrap().len();
+        let failed_at = Instant::now();
+        s.supervisor.tell(msg!(String::from("fail")), None);
+        wait_len(&s.starts, count + 1);
+        assert_eq!(s.starts.lock().unwrap().len(), count + 1);
+        s.starts.lock().unwrap()[count] - failed_at
+    }
+
+    #[test]
+    fn child_is_restarted_after_growing_delay() {
+        let supervisor = BackoffSupervisor::new(Duration::from_millis(100), Duration::from_secs(1))
+            .set_random_factor(0.0)
+            .set_reset_after(Duration::from_secs(10));
+        let mut s = setup(supervisor);
+
+        let delays: Vec<Duration> = (0..3).map(|_| fail_and_measure(&mut s)).collect();
+
+        assert!(delays[0] >= Duration::from_millis(100) && delays[0] < Duration::from_millis(200));
+        assert!(delays[1] >= Duration::from_millis(200) && delays[1] < Duration::from_millis(400));
+        assert!(delays[2] >= Duration::from_millis(400) && delays[2] < Duration::from_millis(800));
+
+        s.system.terminate();
+    }
+
+    #[test]
+    fn messages_is_buffered_while_child_is_absent_and_replayed_after_restart() {
+        let supervisor = BackoffSupervisor::new(Duration::from_millis(200), Duration::from_secs(1))
+            .set_random_factor(0.0)
+            .set_buffer_size(10);
+        let mut s = setup(supervisor);
+
+        s.supervisor.tell(msg!(String::from("fail")), None);
+        thread::sleep(Duration::from_millis(50));
+        for i in 0..3 {
+            s.supervisor.tell(msg!(i as u32), None);
+        }
+        assert_eq!(s.received.lock().unwrap().len(), 0);
+
+        wait_len(&s.received, 3);
+        assert_eq!(s.starts.lock().unwrap().len(), 2);
+        assert_eq!(*s.received.lock().unwrap(), vec![0, 1, 2]);
+
+        s.system.terminate();
+    }
+
+    #[test]
+    fn messages_over_the_buffer_size_is_dropped_to_dead_letters() {
+        let supervisor = BackoffSupervisor::new(Duration::from_millis(200), Duration::from_secs(1))
+            .set_random_factor(0.0)
+            .set_buffer_size(1);
+        let mut s = setup(supervisor);
+
+        let dead = Arc::new(Mutex::new(Vec::new()));
+        let listener = s.system.actor_of(Props::new(tsafe!(Listener { dead: dead.clone() })), None);
+        s.system.event_stream().subscribe::<DeadLetter>(&listener);
+
+        s.supervisor.tell(msg!(String::from("fail")), None);
+        thread::sleep(Duration::from_millis(50));
+        for i in 0..3 {
+            s.supervisor.tell(msg!(i as u32), None);
+        }
+
+        wait_len(&s.received, 1);
+        wait_len(&dead, 2);
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(*s.received.lock().unwrap(), vec![0]);
+        assert_eq!(*dead.lock().unwrap(), vec![1, 2]);
+
+        s.system.terminate();
+    }
+
+    #[test]
+    fn delay_is_reset_after_child_works_for_reset_time() {
+        let supervisor = BackoffSupervisor::new(Duration::from_millis(100), Duration::from_secs(1))
+            .set_random_factor(0.0)
+            .set_reset_after(Duration::from_millis(300));
+        let mut s = setup(supervisor);
+
+        // Child fails right after the start, so delay grows
+        thread::sleep(Duration::from_millis(400));
+        let first = fail_and_measure(&mut s);
+        let second = fail_and_measure(&mut s);
+        assert!(first < Duration::from_millis(200));
+        assert!(second >= Duration::from_millis(200));
+
+        // Child works longer than reset time, so delay is returned to the min backoff
+        thread::sleep(Duration::from_millis(400));
+        let third = fail_and_measure(&mut s);
+        assert!(third >= Duration::from_millis(100) && third < Duration::from_millis(200));
+
+        s.system.terminate();
+    }
+}
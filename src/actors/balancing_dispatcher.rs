@@ -0,0 +1,358 @@
+//! Actor dispatcher with strategy - single mailbox shared between the team of actors
+//!
+//! All actors which uses the same instance of this dispatcher, have the one common mailbox. Each
+//! message sent to any actor of the team, is placed to this mailbox, and will be processed by the
+//! first idle actor. This means that actors of the team must be identical and stateless. Messages
+//! is processed in parallel by different actors, but each actor processes only one message at the
+//! same time.
+use crate::executors::work_stealing_executor::WorkStealingExecutor;
+use crate::executors::executor::{Executor, ExecutorTask};
+use crate::actors::dispatcher::Dispatcher;
+use crate::actors::default_dispatcher::{DefaultDispatcher, DEFAULT_THROUGHPUT};
+use crate::actors::actor_cell::ActorCell;
+use crate::actors::envelope::Envelope;
+use crate::actors::local_actor_ref::LocalActorRef;
+use crate::actors::abstract_actor_ref::AbstractActorRef;
+use crate::actors::mailbox::Mailbox;
+use crate::actors::unbound_mailbox::UnboundMailbox;
+use crate::actors::actor::{Actor, PoisonPill};
+use crate::actors::supervision::SiblingFailed;
+use crate::common::tsafe::TSafe;
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Actor of the team
+#[derive(Clone)]
+struct Member {
+    cell: TSafe<ActorCell>,
+
+    /// Flag which indicates that actor processes messages from the mailbox at this moment
+    busy: Arc<AtomicBool>
+}
+
+pub struct BalancingDispatcher {
+    executor: WorkStealingExecutor,
+
+    /// Mailbox shared between all actors of the team
+    mailbox: TSafe<Mailbox + Send>,
+
+    /// Actors of the team
+    members: TSafe<Vec<Member>>,
+
+    throughput: Arc<AtomicUsize>
+}
+
+impl BalancingDispatcher {
+    pub fn new(t_count: u32) -> BalancingDispatcher {
+        let executor = WorkStealingExecutor::new()
+            .set_threads_count(t_count as usize)
+            .run();
+        BalancingDispatcher {
+            executor,
+            mailbox: tsafe!(UnboundMailbox::new()),
+            members: tsafe!(Vec::new()),
+            throughput: Arc::new(AtomicUsize::new(DEFAULT_THROUGHPUT))
+        }
+    }
+
+    /// Selects idle actor of the team, marks it as busy and returns task which processes the
+    /// mailbox by this actor. If all actors is busy, returns None. Messages will be processed by
+    /// the busy actors later.
+    fn schedule(members: &TSafe<Vec<Member>>, mailbox: &TSafe<Mailbox + Send>, throughput: &Arc<AtomicUsize>) -> Option<ExecutorTask> {
+        let member = {
+            let members = members.lock().unwrap();
+            members.iter()
+                .find(|m| m.busy.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_ok())
+                .map(|m| m.clone())
+        };
+
+        match member {
+            Some(member) => {
+                let members = members.clone();
+                let mailbox = mailbox.clone();
+                let throughput = throughput.clone();
+                Some(Box::new(move || {
+                    BalancingDispatcher::invoke(&member, &members, &mailbox, &throughput)
+                }))
+            },
+            None => None
+        }
+    }
+
+    /// Processes messages from the shared mailbox by the specified actor, but no more than
+    /// throughput value. If after that the mailbox still have messages, next run is planned. If
+    /// actor was stopped, messages will be passed to other actor of the team.
+    fn invoke(member: &Member, members: &TSafe<Vec<Member>>, mailbox: &TSafe<Mailbox + Send>, throughput: &Arc<AtomicUsize>) {
+        let limit = throughput.load(Ordering::Relaxed);
+        let mut processed = 0;
+
+        // Message may be addressed to other actor of the team, but the member must see himself in
+        // the context
+        let path = member.cell.lock().unwrap().path.clone();
+        let self_: Box<AbstractActorRef + Send> = Box::new(LocalActorRef::new(member.cell.clone(), path));
+
+        while processed < limit && !member.cell.lock().unwrap().stopped {
+            let envelope = {
+                let mut mailbox = mailbox.lock().unwrap();
+                if mailbox.has_messages() {
+                    Some(mailbox.dequeue())
+                } else {
+                    None
+                }
+            };
+
+            match envelope {
+                Some(envelope) => DefaultDispatcher::invoke_envelope_as(envelope, mailbox, &member.cell, self_.clone()),
+                None => break
+            }
+
+            processed = processed + 1;
+        }
+
+        let (stopped, dispatcher) = {
+            let cell = member.cell.lock().unwrap();
+            (cell.stopped, cell.dispatcher.clone())
+        };
+
+        let has_messages = {
+            let mailbox = mailbox.lock().unwrap();
+            if mailbox.has_messages() && !stopped {
+                true
+            } else {
+                member.busy.store(false, Ordering::SeqCst);
+                mailbox.has_messages()
+            }
+        };
+
+        if has_messages {
+            let f: Option<ExecutorTask> = if stopped {
+                BalancingDispatcher::schedule(members, mailbox, throughput)
+            } else {
+                let member = member.clone();
+                let members = members.clone();
+                let mailbox = mailbox.clone();
+                let throughput = throughput.clone();
+                Some(Box::new(move || {
+                    BalancingDispatcher::invoke(&member, &members, &mailbox, &throughput)
+                }))
+            };
+
+            if let Some(f) = f {
+                dispatcher.lock().unwrap().execute(f, None);
+            }
+        }
+    }
+}
+
+impl Executor for BalancingDispatcher {
+    fn execute(&mut self, f: ExecutorTask, options: Option<Box<Any>>) {
+        self.executor.execute(f, options)
+    }
+
+    fn stop(&mut self) {
+        self.executor.stop();
+    }
+}
+
+impl Dispatcher for BalancingDispatcher {
+
+    /// Places message to the shared mailbox and plans it processing by the idle actor of the team.
+    /// Actor to which message was sent does not matter. Only exception is PoisonPill and the
+    /// supervision commands, which is passed directly to the actor to which they was sent,
+    /// bypassing the shared mailbox. So they are applied only to this actor and not to the member
+    /// which would dequeue them.
+    fn dispatch(self: &mut Self, cell: TSafe<ActorCell>, _bid: usize, mailbox: TSafe<Mailbox + Send>, _actor: TSafe<Actor + Send>, envelope: Envelope) {
+        if envelope.message.get().is::<PoisonPill>() || envelope.message.get().is::<SiblingFailed>() {
+            let mut envelope = Some(envelope);
+            let f = Box::new(move || {
+                if let Some(envelope) = envelope.take() {
+                    let path = cell.lock().unwrap().path.clone();
+                    let self_: Box<AbstractActorRef + Send> = Box::new(LocalActorRef::new(cell.clone(), path));
+                    DefaultDispatcher::invoke_envelope_as(envelope, &mailbox, &cell, self_)
+                }
+            });
+            self.execute(f, None);
+            return;
+        }
+
+        mailbox.lock().unwrap().enqueue(envelope);
+
+        if let Some(f) = BalancingDispatcher::schedule(&self.members, &mailbox, &self.throughput) {
+            self.execute(f, None);
+        }
+    }
+
+    fn obtain_bid(self: &mut Self) -> usize {
+        0
+    }
+
+    fn set_throughput(self: &mut Self, throughput: usize) {
+        if throughput == 0 {
+            panic!("Throughput of the dispatcher must be greater than zero");
+        }
+        self.throughput.store(throughput, Ordering::Relaxed);
+    }
+
+    fn shared_mailbox(self: &mut Self) -> Option<TSafe<Mailbox + Send>> {
+        Some(self.mailbox.clone())
+    }
+
+    /// Adds actor to the team. If shared mailbox already contains messages, they processing is
+    /// planned on the new actor.
+    fn attach(self: &mut Self, cell: TSafe<ActorCell>, _actor: TSafe<Actor + Send>) {
+        self.members.lock().unwrap().push(Member {
+            cell,
+            busy: Arc::new(AtomicBool::new(false))
+        });
+
+        if self.mailbox.lock().unwrap().has_messages() {
+            if let Some(f) = BalancingDispatcher::schedule(&self.members, &self.mailbox, &self.throughput) {
+                self.execute(f, None);
+            }
+        }
+    }
+
+    fn detach(self: &mut Self, cell: &TSafe<ActorCell>) {
+        self.members.lock().unwrap().retain(|m| !Arc::ptr_eq(&m.cell, cell));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::balancing_dispatcher::BalancingDispatcher;
+    use crate::actors::actor::PoisonPill;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    struct Worker {
+        id: usize,
+        processed: Arc<Mutex<Vec<usize>>>,
+        stopped: Arc<Mutex<Vec<usize>>>
+    }
+
+    impl Actor for Worker {
+        fn post_stop(&mut self, _ctx: ActorContext) {
+            self.stopped.lock().unwrap().push(self.id);
+        }
+
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<u32>() {
+                Some(_) => {
+                    thread::sleep(Duration::from_millis(5));
+                    self.processed.lock().unwrap().push(self.id)
+                },
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    /// Replies to the sender with the own reference and records the path which he sees as own
+    struct Echo {
+        id: usize,
+        seen: Arc<Mutex<Vec<(usize, String)>>>
+    }
+
+    impl Actor for Echo {
+        fn receive(&mut self, msg: Message, mut ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<u32>() {
+                Some(_) => {
+                    thread::sleep(Duration::from_millis(5));
+                    self.seen.lock().unwrap().push((self.id, ctx.self_.path().to_string()));
+                    let self_ = ctx.self_.clone();
+                    ctx.sender.tell(msg!(self.id), Some(&self_));
+                },
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    struct Collector {
+        replies: Arc<Mutex<Vec<(usize, String)>>>
+    }
+
+    impl Actor for Collector {
+        fn receive(&mut self, msg: Message, ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<usize>() {
+                Some(id) => self.replies.lock().unwrap().push((*id, ctx.sender.path().to_string())),
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    fn wait_len(log: &Arc<Mutex<Vec<usize>>>, len: usize) {
+        let start = Instant::now();
+        while log.lock().unwrap().len() < len && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn poison_pill_stops_only_target_member() {
+        let mut system = LocalActorSystem::new();
+        system.add_dispatcher("workers", tsafe!(BalancingDispatcher::new(3)));
+
+        let processed = Arc::new(Mutex::new(Vec::new()));
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+        let mut workers = Vec::new();
+        for id in 0..3 {
+            let actor = tsafe!(Worker { id, processed: processed.clone(), stopped: stopped.clone() });
+            let props = Props::new(actor).with_dispatcher("workers");
+            workers.push(system.actor_of(props, None));
+        }
+
+        for i in 0..30 {
+            workers[0].tell(msg!(i as u32), None);
+        }
+        workers[2].tell(msg!(PoisonPill {}), None);
+
+        wait_len(&stopped, 1);
+        wait_len(&processed, 30);
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(*stopped.lock().unwrap(), vec![2]);
+        assert_eq!(processed.lock().unwrap().len(), 30);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn member_sees_himself_in_the_context() {
+        let mut system = LocalActorSystem::new();
+        system.add_dispatcher("workers", tsafe!(BalancingDispatcher::new(3)));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let replies = Arc::new(Mutex::new(Vec::new()));
+        let mut members = Vec::new();
+        for id in 0..3 {
+            let props = Props::new(tsafe!(Echo { id, seen: seen.clone() })).with_dispatcher("workers");
+            members.push(system.actor_of(props, Some(&format!("m{}", id))));
+        }
+        let collector = system.actor_of(Props::new(tsafe!(Collector { replies: replies.clone() })), None);
+
+        for i in 0..30 {
+            members[0].tell(msg!(i as u32), Some(&collector));
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while replies.lock().unwrap().len() < 30 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let seen = seen.lock().unwrap().clone();
+        let replies = replies.lock().unwrap().clone();
+        assert_eq!(seen.len(), 30);
+        assert_eq!(replies.len(), 30);
+        for (id, path) in seen.iter().chain(replies.iter()) {
+            assert_eq!(*path, format!("/root/m{}", id));
+        }
+        assert!(seen.iter().any(|(id, _)| *id != 0));
+
+        system.terminate();
+    }
+}
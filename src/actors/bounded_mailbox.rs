@@ -0,0 +1,329 @@
+//! Predefined mailbox with queue size restrictions
+//!
+//! This mailbox may contain only specified count of messages. What to do with a message which
+//! does not fit into the queue, is determined by the overflow strategy. Use it for actors which
+//! may be flooded by the messages from faster producers.
+//!
+use crate::actors::mailbox::{Mailbox, MailboxGate};
+use crate::actors::envelope::Envelope;
+use crate::actors::abstract_actor_ref::ActorRef;
+use std::collections::vec_deque::VecDeque;
+use std::time::Duration;
+
+/// Defines what to do with a message, when the mailbox is full
+#[derive(Clone, Debug, PartialEq)]
+pub enum OverflowStrategy {
+
+    /// New message is discarded
+    DropNewest,
+
+    /// Oldest message in the queue is discarded for make place to the new one
+    DropOldest,
+
+    /// New message is dropped to the DeadLetters
+    DeadLetters,
+
+    /// Sender is blocked until the mailbox will have free space, but no more than specified
+    /// timeout. If timeout is reached, new message is dropped to the DeadLetters. Only tell calls
+    /// is blocked, system messages and messages sent from the thread which processes this mailbox
+    /// is dropped to the DeadLetters immediately.
+    BlockSender(Duration)
+}
+
+pub struct BoundedMailbox {
+    is_planned: bool,
+    queue: VecDeque<Envelope>,
+    capacity: usize,
+    strategy: OverflowStrategy,
+    rejected: Vec<Envelope>,
+    blocked: Vec<Envelope>,
+    gate: MailboxGate
+}
+
+impl BoundedMailbox {
+    pub fn new(capacity: usize, strategy: OverflowStrategy) -> BoundedMailbox {
+        if capacity == 0 {
+            panic!("Capacity of the bounded mailbox must be greater than zero")
+        }
+
+        BoundedMailbox {
+            is_planned: false,
+            queue: VecDeque::new(),
+            capacity,
+            rejected: Vec::new(),
+            blocked: Vec::new(),
+            gate: MailboxGate::new(capacity, match strategy {
+                OverflowStrategy::BlockSender(timeout) => timeout,
+                _ => Duration::from_secs(0)
+            }),
+            strategy
+        }
+    }
+
+    /// Publishes current size of the queue to the blocked senders
+    fn update_size(&self) {
+        if let OverflowStrategy::BlockSender(_) = self.strategy {
+            self.gate.update_size(self.queue.len());
+        }
+    }
+}
+
+impl Mailbox for BoundedMailbox {
+
+    fn set_planned(self: &mut Self, planned: bool) {
+        self.is_planned = planned;
+    }
+
+    fn is_planned(self: &Self) -> bool {
+        self.is_planned
+    }
+
+    /// Enqueue new message. If the queue is full, message is processed according to the overflow
+    /// strategy.
+    fn enqueue(self: &mut Self, envelope: Envelope) {
+        if self.queue.len() < self.capacity {
+            self.queue.push_back(envelope);
+        } else {
+            match self.strategy {
+                OverflowStrategy::DropNewest => {
+                    debug!("Bounded mailbox of {} is full, new message was dropped", envelope.receiver);
+                },
+                OverflowStrategy::DropOldest => {
+                    debug!("Bounded mailbox of {} is full, oldest message was dropped", envelope.receiver);
+                    self.queue.pop_front();
+                    self.queue.push_back(envelope);
+                },
+                OverflowStrategy::DeadLetters => {
+                    self.rejected.push(envelope);
+                },
+                OverflowStrategy::BlockSender(_) => {
+                    self.blocked.push(envelope);
+                }
+            }
+        }
+
+        self.update_size();
+    }
+
+    fn dequeue(self: &mut Self) -> Envelope {
+        let envelope = self.queue.pop_front().unwrap();
+        if let OverflowStrategy::BlockSender(_) = self.strategy {
+            self.gate.set_owner();
+        }
+        self.update_size();
+        envelope
+    }
+
+    fn has_messages(self: &Self) -> bool {
+        self.queue.len() > 0
+    }
+
+    fn size(self: &Self) -> usize {
+        self.queue.len()
+    }
+
+    /// Drops all messages to the DeadLetter
+    fn clean_up(self: &mut Self, sender: ActorRef, dead_letters: ActorRef) {
+        let mut dead_letters = dead_letters;
+
+        while let Some(envelope) = self.queue.pop_front() {
+            dead_letters.tell(envelope.message, Some(&sender));
+        }
+
+        self.update_size();
+    }
+
+    fn drain_rejected(self: &mut Self) -> Vec<Envelope> {
+        self.rejected.drain(..).collect()
+    }
+
+    fn drain_blocked(self: &mut Self) -> Vec<Envelope> {
+        self.blocked.drain(..).collect()
+    }
+
+    /// Returns gate only for the BlockSender strategy
+    fn gate(self: &Self) -> Option<MailboxGate> {
+        match self.strategy {
+            OverflowStrategy::BlockSender(_) => Some(self.gate.clone()),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::bounded_mailbox::{BoundedMailbox, OverflowStrategy};
+    use crate::actors::mailbox::Mailbox;
+    use crate::actors::envelope::Envelope;
+    use crate::common::tsafe::TSafe;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    fn envelope(system: &mut LocalActorSystem, n: u32) -> Envelope {
+        let boxed: TSafe<AbstractActorSystem + Send> = tsafe!(system.clone());
+        Envelope::new(msg!(n), None, system.dead_letters(), boxed)
+    }
+
+    fn fill(strategy: OverflowStrategy) -> (BoundedMailbox, Vec<u32>) {
+        let mut system = LocalActorSystem::new();
+        let mut mailbox = BoundedMailbox::new(2, strategy);
+        for n in 1..4 {
+            let envelope = envelope(&mut system, n);
+            mailbox.enqueue(envelope);
+        }
+
+        let mut queued = Vec::new();
+        while mailbox.has_messages() {
+            queued.push(*mailbox.dequeue().message.get().downcast_ref::<u32>().unwrap());
+        }
+
+        (mailbox, queued)
+    }
+
+    fn numbers(envelopes: Vec<Envelope>) -> Vec<u32> {
+        envelopes.iter().map(|e| *e.message.get().downcast_ref::<u32>().unwrap()).collect()
+    }
+
+    #[test]
+    fn drop_newest_discards_new_message() {
+        let (mut mailbox, queued) = fill(OverflowStrategy::DropNewest);
+        assert_eq!(queued, vec![1, 2]);
+        assert_eq!(mailbox.drain_rejected().len(), 0);
+    }
+
+    #[test]
+    fn drop_oldest_discards_head_of_queue() {
+        let (mut mailbox, queued) = fill(OverflowStrategy::DropOldest);
+        assert_eq!(queued, vec![2, 3]);
+        assert_eq!(mailbox.drain_rejected().len(), 0);
+    }
+
+    #[test]
+    fn dead_letters_strategy_rejects_new_message() {
+        let (mut mailbox, queued) = fill(OverflowStrategy::DeadLetters);
+        assert_eq!(queued, vec![1, 2]);
+        assert_eq!(numbers(mailbox.drain_rejected()), vec![3]);
+        assert_eq!(mailbox.drain_blocked().len(), 0);
+    }
+
+    #[test]
+    fn block_sender_strategy_returns_new_message_to_sender() {
+        let (mut mailbox, queued) = fill(OverflowStrategy::BlockSender(Duration::from_millis(100)));
+        assert_eq!(queued, vec![1, 2]);
+        assert_eq!(mailbox.drain_rejected().len(), 0);
+        assert_eq!(numbers(mailbox.drain_blocked()), vec![3]);
+    }
+
+    /// Waits while paused before processing of each message
+    struct Slow {
+        paused: Arc<AtomicBool>,
+        log: Arc<Mutex<Vec<u32>>>
+    }
+
+    impl Actor for Slow {
+        fn receive(&mut self, msg: Message, mut ctx: ActorContext) -> HandleResult {
+            while self.paused.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            match msg.get().downcast_ref::<u32>() {
+                Some(n) => self.log.lock().unwrap().push(*n),
+                None => {
+                    // Mailbox of the actor is processed only by this thread, so it's not waited
+                    let start = Instant::now();
+                    ctx.self_.tell(msg!(1 as u32), None);
+                    ctx.self_.tell(msg!(2 as u32), None);
+                    self.log.lock().unwrap().push(start.elapsed().as_millis() as u32);
+                }
+            }
+            Ok(true)
+        }
+    }
+
+    fn slow(system: &mut LocalActorSystem, timeout: Duration) -> (ActorRef, Arc<AtomicBool>, Arc<Mutex<Vec<u32>>>) {
+        let paused = Arc::new(AtomicBool::new(false));
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let props = Props::new(tsafe!(Slow { paused: paused.clone(), log: log.clone() }))
+            .with_bounded_mailbox(1, OverflowStrategy::BlockSender(timeout));
+        let actor = system.actor_of(props, None);
+        (actor, paused, log)
+    }
+
+    fn wait_log(log: &Arc<Mutex<Vec<u32>>>, len: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < len && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn blocked_sender_sends_message_after_space_was_freed() {
+        let mut system = LocalActorSystem::new();
+        let (mut actor, paused, log) = slow(&mut system, Duration::from_secs(5));
+
+        paused.store(true, Ordering::SeqCst);
+        actor.tell(msg!(1 as u32), None);
+        thread::sleep(Duration::from_millis(50));
+        actor.tell(msg!(2 as u32), None);
+
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            paused.store(false, Ordering::SeqCst);
+        });
+
+        let start = Instant::now();
+        actor.tell(msg!(3 as u32), None);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        releaser.join().unwrap();
+
+        wait_log(&log, 3);
+        assert_eq!(*log.lock().unwrap(), vec![1, 2, 3]);
+        assert_eq!(system.dead_letters_count(), 0);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn blocked_sender_drops_message_to_dead_letters_at_timeout() {
+        let mut system = LocalActorSystem::new();
+        let (mut actor, paused, log) = slow(&mut system, Duration::from_millis(100));
+
+        paused.store(true, Ordering::SeqCst);
+        actor.tell(msg!(1 as u32), None);
+        thread::sleep(Duration::from_millis(50));
+
+        // Several senders can't overflow the mailbox, each of them waits for free space
+        let senders: Vec<_> = (2..5).map(|n| {
+            let mut actor = actor.clone();
+            thread::spawn(move || actor.tell(msg!(n as u32), None))
+        }).collect();
+        for sender in senders {
+            sender.join().unwrap();
+        }
+        paused.store(false, Ordering::SeqCst);
+
+        wait_log(&log, 2);
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(log.lock().unwrap().len(), 2);
+        assert_eq!(system.dead_letters_count(), 2);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn sender_processing_the_mailbox_is_not_blocked() {
+        let mut system = LocalActorSystem::new();
+        let (mut actor, _paused, log) = slow(&mut system, Duration::from_secs(5));
+
+        actor.tell(msg!("self"), None);
+
+        wait_log(&log, 2);
+        let elapsed = log.lock().unwrap()[0];
+        assert!(elapsed < 1000);
+        assert_eq!(system.dead_letters_count(), 1);
+
+        system.terminate();
+    }
+}
@@ -0,0 +1,276 @@
+//! Actor dispatcher with strategy - messages is processed on the thread which sent them
+//!
+//! This dispatcher does not have his own threads. Message is processed synchronously on the thread
+//! which call tell, right before return from this call. This makes actor's behavior fully
+//! deterministic, that is very useful in tests. After tell, actor's state already contains results
+//! of the message processing and may be checked without any waiting.
+//!
+//! Message can not be processed inside the dispatch method, because at this moment actor cell and
+//! dispatcher is locked. Instead this, dispatcher places the processing task to the queue of the
+//! current thread, and this queue is run by the ActorCell::send_and_run, after the cell was
+//! released. This function is used by the tell method of the actor references, so messages sent
+//! from timers, watchers, event stream and any others places is processed in the same way. If actor
+//! sends a message during processing of other message on the same thread, new message will be
+//! processed after the current message processing will be completed. This protects actor from
+//! reentrancy to the receive method.
+//!
+//! Tasks also is not run while the current thread holds the lock of the actor system, taken through
+//! ActorContext::system or by internals of the library, because processed message may need the same
+//! system. They will be run right after the lock is released.
+use crate::executors::executor::{Executor, ExecutorTask};
+use crate::actors::dispatcher::Dispatcher;
+use crate::actors::default_dispatcher::DefaultDispatcher;
+use crate::actors::actor_cell::ActorCell;
+use crate::actors::envelope::Envelope;
+use crate::actors::mailbox::Mailbox;
+use crate::actors::actor::Actor;
+use crate::common::tsafe::TSafe;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::vec_deque::VecDeque;
+use std::thread;
+
+thread_local! {
+    /// Tasks planned on the current thread
+    static PENDING: RefCell<VecDeque<ExecutorTask>> = RefCell::new(VecDeque::new());
+
+    /// Flag which indicates that the current thread already runs the planned tasks
+    static RUNNING: Cell<bool> = Cell::new(false);
+
+    /// Count of the active holds on the current thread
+    static HELD: Cell<usize> = Cell::new(0);
+}
+
+pub struct CallingThreadDispatcher {}
+
+impl CallingThreadDispatcher {
+    pub fn new() -> CallingThreadDispatcher {
+        CallingThreadDispatcher {}
+    }
+
+    /// Runs all tasks planned on the current thread. If this thread already runs tasks (message
+    /// was sent from the receive method), does nothing, because new tasks will be run by the outer
+    /// call. Must be called only when no one actor's lock is held by the current thread.
+    pub fn run_pending() {
+        if RUNNING.with(|r| r.get()) || HELD.with(|h| h.get()) > 0 {
+            return;
+        }
+
+        RUNNING.with(|r| r.set(true));
+        let _guard = RunningGuard {};
+
+        loop {
+            let task = PENDING.with(|p| p.borrow_mut().pop_front());
+            match task {
+                Some(mut f) => f(),
+                None => break
+            }
+        }
+    }
+
+    /// Prohibits running of the planned tasks on the current thread until the returned hold will
+    /// be dropped. Used when the actor system is locked, so the hold must be created before the
+    /// lock and dropped after it. Tasks planned in the meantime is run when the last hold is
+    /// dropped.
+    pub fn hold() -> PendingHold {
+        HELD.with(|h| h.set(h.get() + 1));
+        PendingHold {}
+    }
+}
+
+pub struct PendingHold {}
+
+impl Drop for PendingHold {
+    fn drop(&mut self) {
+        let held = HELD.with(|h| {
+            h.set(h.get() - 1);
+            h.get()
+        });
+
+        // Tasks is not run at unwinding, they will be run by the next tell on this thread
+        if held == 0 && !thread::panicking() {
+            CallingThreadDispatcher::run_pending();
+        }
+    }
+}
+
+/// Resets the running flag of the current thread, even if some task was panicked. Otherwise
+/// messages planned on this thread will never be processed.
+struct RunningGuard {}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.with(|r| r.set(false));
+    }
+}
+
+impl Executor for CallingThreadDispatcher {
+
+    /// Plans task on the current thread. Task will be run at the end of the nearest tell call
+    /// performed on this thread.
+    fn execute(&mut self, f: ExecutorTask, _options: Option<Box<Any>>) {
+        PENDING.with(|p| p.borrow_mut().push_back(f));
+    }
+
+    fn stop(&mut self) {}
+}
+
+impl Dispatcher for CallingThreadDispatcher {
+
+    fn dispatch(self: &mut Self, cell: TSafe<ActorCell>, _bid: usize, mailbox: TSafe<Mailbox + Send>, _actor: TSafe<Actor + Send>, envelope: Envelope) {
+        mailbox.lock().unwrap().enqueue(envelope);
+
+        let f = Box::new(move || {
+            let envelope = {
+                let mut mailbox = mailbox.lock().unwrap();
+                if mailbox.has_messages() {
+                    Some(mailbox.dequeue())
+                } else {
+                    None
+                }
+            };
+
+            if let Some(envelope) = envelope {
+                DefaultDispatcher::invoke_envelope(envelope, &mailbox, &cell);
+            }
+        });
+
+        self.execute(f, None);
+    }
+
+    fn obtain_bid(self: &mut Self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::calling_thread_dispatcher::CallingThreadDispatcher;
+    use crate::actors::watcher::events::Terminated;
+    use crate::executors::executor::Executor;
+    use crate::common::tsafe::TSafe;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+    use std::panic;
+
+    struct Recorder {
+        log: Arc<Mutex<Vec<u32>>>
+    }
+
+    impl Actor for Recorder {
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<u32>() {
+                Some(v) => self.log.lock().unwrap().push(*v),
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    struct Forwarder {
+        target: ActorRef
+    }
+
+    impl Actor for Forwarder {
+        fn receive(&mut self, msg: Message, ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<u32>() {
+                Some(v) => self.target.tell(msg!(*v + 1), Some(&ctx.self_)),
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    /// Touches the actor system when the watched child is terminated. Child shares the system
+    /// with his parent.
+    struct Mourner {
+        child: Arc<Mutex<Option<ActorRef>>>,
+        log: Arc<Mutex<Vec<u32>>>
+    }
+
+    impl Actor for Mourner {
+        fn pre_start(&mut self, mut ctx: ActorContext) {
+            let child = ctx.actor_of(Props::new(tsafe!(Recorder { log: Arc::new(Mutex::new(Vec::new())) })), Some("child"));
+            ctx.system().watch(&ctx.self_, &child);
+            *self.child.lock().unwrap() = Some(child);
+        }
+
+        fn receive(&mut self, msg: Message, ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<Terminated>() {
+                Some(_) => {
+                    let _ = ctx.system().dead_letters();
+                    self.log.lock().unwrap().push(0)
+                },
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    fn system() -> TSafe<LocalActorSystem> {
+        let system = tsafe!(LocalActorSystem::new());
+        system.lock().unwrap().add_dispatcher("calling_thread", tsafe!(CallingThreadDispatcher::new()));
+        system
+    }
+
+    #[test]
+    fn actor_state_is_updated_right_after_tell() {
+        let system = system();
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let props = Props::new(tsafe!(Recorder { log: log.clone() })).with_dispatcher("calling_thread");
+        let recorder = system.lock().unwrap().actor_of(props, None);
+        let props = Props::new(tsafe!(Forwarder { target: recorder.clone() })).with_dispatcher("calling_thread");
+        let mut forwarder = system.lock().unwrap().actor_of(props, None);
+
+        forwarder.tell(msg!(1 as u32), None);
+        assert_eq!(*log.lock().unwrap(), vec![2]);
+
+        forwarder.tell(msg!(5 as u32), None);
+        assert_eq!(*log.lock().unwrap(), vec![2, 6]);
+
+        system.lock().unwrap().terminate();
+    }
+
+    #[test]
+    fn messages_is_processed_after_panicked_task() {
+        let system = system();
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let props = Props::new(tsafe!(Recorder { log: log.clone() })).with_dispatcher("calling_thread");
+        let mut recorder = system.lock().unwrap().actor_of(props, None);
+
+        let mut dispatcher = CallingThreadDispatcher::new();
+        dispatcher.execute(Box::new(|| panic!("Task failure")), None);
+        let result = panic::catch_unwind(|| CallingThreadDispatcher::run_pending());
+        assert!(result.is_err());
+
+        recorder.tell(msg!(1 as u32), None);
+        assert_eq!(*log.lock().unwrap(), vec![1]);
+
+        system.lock().unwrap().terminate();
+    }
+
+    #[test]
+    fn messages_sent_under_system_lock_is_processed_after_unlock() {
+        let system = system();
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let child = Arc::new(Mutex::new(None));
+        let props = Props::new(tsafe!(Mourner { child: child.clone(), log: log.clone() })).with_dispatcher("calling_thread");
+        let _mourner = system.lock().unwrap().actor_of(props, None);
+
+        let mut child = child.lock().unwrap().take().unwrap();
+        system.lock().unwrap().stop(&mut child);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(*log.lock().unwrap(), vec![0]);
+
+        system.lock().unwrap().terminate();
+    }
+}
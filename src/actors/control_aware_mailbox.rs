@@ -0,0 +1,195 @@
+//! Predefined mailbox which processes control messages before any other messages
+//!
+//! Mailbox contains two queues - for control messages and for all others. Messages from the
+//! control queue always dequeued first. By default, control messages is PoisonPill and watching
+//! event Terminated. Type of the message can not be checked for some trait at runtime, so other
+//! types of messages must be marked with ControlMessage trait and explicitly registered as control
+//! through with_control call. In this case mailbox must be registered in the actor system as named
+//! mailbox:
+//!
+//! ```ignore
+//! impl ControlMessage for Cancel {}
+//!
+//! system.add_mailbox("control", Box::new(|| tsafe!(
+//!     ControlAwareMailbox::new()
+//!         .with_control::<Cancel>())));
+//! ```
+//!
+use crate::actors::mailbox::Mailbox;
+use crate::actors::envelope::Envelope;
+use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::message::{Message, ControlMessage};
+use std::any::Any;
+use crate::actors::actor::PoisonPill;
+use crate::actors::watcher::events::Terminated;
+use std::collections::vec_deque::VecDeque;
+
+pub struct ControlAwareMailbox {
+    is_planned: bool,
+    control_queue: VecDeque<Envelope>,
+    queue: VecDeque<Envelope>,
+    matchers: Vec<Box<Fn(&Message) -> bool + Send>>
+}
+
+impl ControlAwareMailbox {
+
+    /// Creates new mailbox with default set of control messages
+    pub fn new() -> ControlAwareMailbox {
+        ControlAwareMailbox {
+            is_planned: false,
+            control_queue: VecDeque::new(),
+            queue: VecDeque::new(),
+            matchers: Vec::new()
+        }
+            .with_control::<PoisonPill>()
+            .with_control::<Terminated>()
+    }
+
+    /// Adds message type to the set of control messages
+    pub fn with_control<T: ControlMessage + Any + Send>(mut self) -> ControlAwareMailbox {
+        self.matchers.push(Box::new(|msg: &Message| msg.get().is::<T>()));
+        self
+    }
+
+    fn is_control(&self, msg: &Message) -> bool {
+        self.matchers.iter().any(|m| m(msg))
+    }
+}
+
+impl Mailbox for ControlAwareMailbox {
+
+    fn set_planned(self: &mut Self, planned: bool) {
+        self.is_planned = planned;
+    }
+
+    fn is_planned(self: &Self) -> bool {
+        self.is_planned
+    }
+
+    fn enqueue(self: &mut Self, envelope: Envelope) {
+        if self.is_control(&envelope.message) {
+            self.control_queue.push_back(envelope);
+        } else {
+            self.queue.push_back(envelope);
+        }
+    }
+
+    /// Dequeue message from the control queue, and only if it is empty, from the regular queue
+    fn dequeue(self: &mut Self) -> Envelope {
+        match self.control_queue.pop_front() {
+            Some(envelope) => envelope,
+            None => self.queue.pop_front().unwrap()
+        }
+    }
+
+    fn has_messages(self: &Self) -> bool {
+        self.control_queue.len() > 0 || self.queue.len() > 0
+    }
+
+    fn size(self: &Self) -> usize {
+        self.control_queue.len() + self.queue.len()
+    }
+
+    /// Drops all messages to the DeadLetter
+    fn clean_up(self: &mut Self, sender: ActorRef, dead_letters: ActorRef) {
+        let mut dead_letters = dead_letters;
+
+        while self.has_messages() {
+            let envelope = self.dequeue();
+            dead_letters.tell(envelope.message, Some(&sender));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::control_aware_mailbox::ControlAwareMailbox;
+    use crate::actors::mailbox::Mailbox;
+    use crate::actors::envelope::Envelope;
+    use crate::actors::actor::PoisonPill;
+    use crate::common::tsafe::TSafe;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    struct Cancel;
+
+    impl ControlMessage for Cancel {}
+
+    #[test]
+    fn control_messages_is_dequeued_before_others() {
+        let mut system = LocalActorSystem::new();
+        let mut mailbox = ControlAwareMailbox::new().with_control::<Cancel>();
+
+        let messages = vec![msg!(1 as u32), msg!(PoisonPill {}), msg!(2 as u32), msg!(Cancel)];
+        for msg in messages {
+            let boxed: TSafe<AbstractActorSystem + Send> = tsafe!(system.clone());
+            mailbox.enqueue(Envelope::new(msg, None, system.dead_letters(), boxed));
+        }
+
+        let mut order = Vec::new();
+        while mailbox.has_messages() {
+            let envelope = mailbox.dequeue();
+            let msg = envelope.message.get();
+            order.push(match msg.downcast_ref::<u32>() {
+                Some(n) => n.to_string(),
+                None if msg.is::<PoisonPill>() => String::from("pill"),
+                None => String::from("cancel")
+            });
+        }
+
+        assert_eq!(order, vec!["pill", "cancel", "1", "2"]);
+        system.terminate();
+    }
+
+    /// Waits while paused before processing of each message
+    struct Worker {
+        paused: Arc<AtomicBool>,
+        log: Arc<Mutex<Vec<String>>>
+    }
+
+    impl Actor for Worker {
+        fn post_stop(&mut self, _ctx: ActorContext) {
+            self.log.lock().unwrap().push(String::from("stopped"));
+        }
+
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            while self.paused.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            match msg.get().downcast_ref::<u32>() {
+                Some(n) => self.log.lock().unwrap().push(n.to_string()),
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn poison_pill_jumps_ahead_of_queued_messages() {
+        let mut system = LocalActorSystem::new();
+        let paused = Arc::new(AtomicBool::new(true));
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let props = Props::new(tsafe!(Worker { paused: paused.clone(), log: log.clone() }))
+            .with_control_aware_mailbox();
+        let mut worker = system.actor_of(props, None);
+
+        for n in 1..5 {
+            worker.tell(msg!(n as u32), None);
+        }
+        thread::sleep(Duration::from_millis(50));
+        worker.tell(msg!(PoisonPill {}), None);
+        paused.store(false, Ordering::SeqCst);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < 2 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(*log.lock().unwrap(), vec!["1", "stopped"]);
+        system.terminate();
+    }
+}
@@ -0,0 +1,395 @@
+//! Coordinated shutdown of the actor system
+//!
+//! Shutdown is performed through the ordered list of named phases. Each phase contains the tasks,
+//! which is registered to it by the user code. Tasks of the one phase is run in parallel, and the
+//! next phase is started only after all tasks of the previous phase was completed, or after the
+//! phase timeout was reached. After the last phase, actor system stops all actors and dispatchers.
+//!
+//! Shutdown is run by the terminate call of the actor system, by the run function, or by the
+//! SIGTERM / SIGINT signals, if signals handling was enabled (Linux only). Phases is run in the
+//! separate thread, so the caller may hold the lock of the actor system. Completion of the shutdown
+//! may be awaited through the returned future.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let shutdown = system.coordinated_shutdown();
+//! shutdown.lock().unwrap().add_task(PHASE_SERVICE_REQUESTS_DONE, "drain-storage", move || {
+//!     storage.ask(&mut (*system), msg!(storage::commands::Flush {})).wait();
+//! });
+//!
+//! CoordinatedShutdown::enable_signals(&shutdown, true);
+//! ```
+
+use crate::common::tsafe::TSafe;
+use crate::futures::completable_promise::CompletablePromise;
+use crate::futures::promise::Promise;
+use crate::futures::future::WrappedFuture;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::thread;
+use std::mem;
+
+/// Stop accepting of new remote connections
+pub const PHASE_SERVICE_UNBIND: &str = "service-unbind";
+
+/// Waiting of completion of requests which is processed by the service actors
+pub const PHASE_SERVICE_REQUESTS_DONE: &str = "service-requests-done";
+
+/// Stopping of the service actors
+pub const PHASE_SERVICE_STOP: &str = "service-stop";
+
+/// Flushing of the buffers and any other work which must be done before actors will be stopped
+pub const PHASE_FLUSH: &str = "flush";
+
+/// Last phase, after which all actors and dispatchers is stopped
+pub const PHASE_ACTOR_SYSTEM_TERMINATE: &str = "actor-system-terminate";
+
+/// Default timeout of the phase
+const DEFAULT_PHASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Task of the shutdown phase
+pub type ShutdownTask = Box<FnMut() + Send>;
+
+struct Phase {
+
+    /// Name of the phase
+    name: String,
+
+    /// Maximum time of the phase execution
+    timeout: Duration,
+
+    /// Named tasks of the phase
+    tasks: Vec<(String, ShutdownTask)>
+}
+
+pub struct CoordinatedShutdown {
+
+    /// Phases in the order of execution
+    phases: Vec<Phase>,
+
+    /// Function which stops actors and dispatchers of the actor system. Set up by the system.
+    terminator: Option<ShutdownTask>,
+
+    /// Is shutdown already was started
+    started: bool,
+
+    /// Completed after the actor system was stopped
+    done: TSafe<CompletablePromise<(), ()>>
+}
+
+impl CoordinatedShutdown {
+    pub fn new() -> CoordinatedShutdown {
+        let names = [
+            PHASE_SERVICE_UNBIND,
+            PHASE_SERVICE_REQUESTS_DONE,
+            PHASE_SERVICE_STOP,
+            PHASE_FLUSH,
+            PHASE_ACTOR_SYSTEM_TERMINATE
+        ];
+
+        let phases = names.iter().map(|name| Phase {
+            name: String::from(*name),
+            timeout: DEFAULT_PHASE_TIMEOUT,
+            tasks: Vec::new()
+        }).collect();
+
+        CoordinatedShutdown {
+            phases,
+            terminator: None,
+            started: false,
+            done: tsafe!(CompletablePromise::new())
+        }
+    }
+
+    /// Registers task in the specified phase. Task must block the caller until his work will be
+    /// completed. Panics if phase does not exists or shutdown already was started.
+    pub fn add_task<F>(&mut self, phase: &str, name: &str, f: F)
+        where F: FnMut() + Send + 'static {
+        if self.started {
+            panic!("Unable to add task '{}' to the coordinated shutdown, because it already was started", name);
+        }
+
+        self.get_phase(phase).tasks.push((String::from(name), Box::new(f)));
+    }
+
+    /// Sets maximum time of the phase execution. 5 seconds by default. Panics if phase does not
+    /// exists.
+    pub fn set_phase_timeout(&mut self, phase: &str, timeout: Duration) {
+        self.get_phase(phase).timeout = timeout;
+    }
+
+    /// Sets function which stops the actor system after the last phase. Used by actor systems.
+    pub fn set_terminator<F>(&mut self, f: F)
+        where F: FnMut() + Send + 'static {
+        self.terminator = Some(Box::new(f));
+    }
+
+    /// Is shutdown already was started
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Runs all phases in order and then stops the actor system. Phases is run in the separate
+    /// thread, caller is not blocked. Returned future is completed after the actor system was
+    /// stopped. Repeated calls does not run shutdown again and returns the same future.
+    pub fn run(shutdown: &TSafe<CoordinatedShutdown>) -> WrappedFuture<(), ()> {
+        let (phases, terminator, done) = {
+            let mut shutdown = shutdown.lock().unwrap();
+            if shutdown.started {
+                return shutdown.done.lock().unwrap().future();
+            }
+            shutdown.started = true;
+            (mem::replace(&mut shutdown.phases, Vec::new()), shutdown.terminator.take(), shutdown.done.clone())
+        };
+
+        let future = done.lock().unwrap().future();
+
+        thread::spawn(move || {
+            for phase in phases {
+                CoordinatedShutdown::run_phase(phase);
+            }
+
+            if let Some(mut terminator) = terminator {
+                terminator();
+            }
+
+            done.lock().unwrap().success(());
+        });
+
+        future
+    }
+
+    /// Runs shutdown when the process receives SIGTERM or SIGINT signal. If exit is true, after
+    /// shutdown process is exited with code 128 + signal number. Only one shutdown may listen
+    /// signals in the process.
+    #[cfg(target_os = "linux")]
+    pub fn enable_signals(shutdown: &TSafe<CoordinatedShutdown>, exit: bool) {
+        let shutdown = shutdown.clone();
+        signals::listen(move |signal| {
+            info!("Received signal {}, run coordinated shutdown", signal);
+            let mut done = CoordinatedShutdown::run(&shutdown);
+            if exit {
+                while !done.ready(Duration::from_secs(1)) {}
+                std::process::exit(128 + signal);
+            }
+        });
+    }
+
+    /// Signals handling is supported only on Linux
+    #[cfg(not(target_os = "linux"))]
+    pub fn enable_signals(_shutdown: &TSafe<CoordinatedShutdown>, _exit: bool) {
+        warn!("Coordinated shutdown by signals is not supported on this platform");
+    }
+
+    /// Runs all tasks of the phase, each in separate thread, and waits their completion or timeout.
+    /// Tasks which was not completed at the timeout is abandoned.
+    fn run_phase(phase: Phase) {
+        if phase.tasks.len() == 0 {
+            return;
+        }
+
+        debug!("Coordinated shutdown runs phase '{}'", phase.name);
+
+        let (sender, receiver) = mpsc::channel();
+        let total = phase.tasks.len();
+        for (name, mut task) in phase.tasks {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                task();
+                let _ = sender.send(name);
+            });
+        }
+        drop(sender);
+
+        let deadline = Instant::now() + phase.timeout;
+        let mut completed = 0;
+        while completed < total {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+
+            match receiver.recv_timeout(deadline - now) {
+                Ok(name) => {
+                    trace!("Coordinated shutdown task '{}' was completed", name);
+                    completed = completed + 1;
+                },
+                Err(RecvTimeoutError::Timeout) => break,
+
+                // All remaining tasks was panicked
+                Err(RecvTimeoutError::Disconnected) => break
+            }
+        }
+
+        if completed < total {
+            warn!("Phase '{}' of the coordinated shutdown was not completed, {} of {} tasks is not finished", phase.name, total - completed, total);
+        }
+    }
+
+    fn get_phase(&mut self, name: &str) -> &mut Phase {
+        match self.phases.iter_mut().find(|p| p.name == name) {
+            Some(phase) => phase,
+            None => panic!("Coordinated shutdown phase '{}' does not exists", name)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod signals {
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::thread;
+
+    /// Write end of the pipe through which signal handler notifies the listener thread
+    static PIPE: AtomicI32 = AtomicI32::new(-1);
+
+    /// Signal handler. Only writes the signal number to the pipe, because this is async-signal-safe.
+    extern "C" fn on_signal(signal: libc::c_int) {
+        let fd = PIPE.load(Ordering::SeqCst);
+        if fd >= 0 {
+            let byte = signal as u8;
+            unsafe {
+                libc::write(fd, &byte as *const u8 as *const libc::c_void, 1);
+            }
+        }
+    }
+
+    /// Installs handlers of SIGTERM and SIGINT and calls f in the separate thread after the first
+    /// received signal. After that, default handlers is restored.
+    pub fn listen<F>(f: F) where F: FnOnce(i32) + Send + 'static {
+        let mut fds = [0 as libc::c_int; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            panic!("Unable to create pipe for signals handling");
+        }
+
+        if PIPE.compare_exchange(-1, fds[1], Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            unsafe {
+                libc::close(fds[0]);
+                libc::close(fds[1]);
+            }
+            panic!("Signals handling already was enabled by other coordinated shutdown");
+        }
+
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        unsafe {
+            libc::signal(libc::SIGTERM, handler);
+            libc::signal(libc::SIGINT, handler);
+        }
+
+        let read_fd = fds[0];
+        thread::spawn(move || {
+            let mut byte: u8 = 0;
+            loop {
+                let n = unsafe { libc::read(read_fd, &mut byte as *mut u8 as *mut libc::c_void, 1) };
+                if n == 1 {
+                    break;
+                }
+            }
+
+            // Repeated signal kills the process immediately, if shutdown is hanged
+            unsafe {
+                libc::signal(libc::SIGTERM, libc::SIG_DFL);
+                libc::signal(libc::SIGINT, libc::SIG_DFL);
+            }
+
+            f(byte as i32);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::coordinated_shutdown::PHASE_FLUSH;
+    use crate::actors::watcher::StopReason;
+    use crate::actors::watcher::events::Terminated;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    struct Listener {
+        paths: Arc<Mutex<Vec<String>>>
+    }
+
+    impl Actor for Listener {
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<Terminated>() {
+                Some(event) => {
+                    if event.reason == StopReason::SystemShutdown {
+                        self.paths.lock().unwrap().push(event.actor.path().to_string());
+                    }
+                },
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn terminate_blocks_until_system_is_stopped() {
+        let mut system = LocalActorSystem::new();
+        let done = Arc::new(Mutex::new(false));
+        let task_done = done.clone();
+        system.coordinated_shutdown().lock().unwrap().add_task(PHASE_FLUSH, "slow", move || {
+            std::thread::sleep(Duration::from_millis(200));
+            *task_done.lock().unwrap() = true;
+        });
+        let mut actor = system.actor_of(Props::new(tsafe!(Listener { paths: Arc::new(Mutex::new(Vec::new())) })), None);
+
+        system.terminate();
+
+        assert!(*done.lock().unwrap());
+        assert!(actor.cell().lock().unwrap().stopped);
+    }
+
+    #[test]
+    fn task_may_lock_system_while_terminate_async_caller_holds_it() {
+        let system = tsafe!(LocalActorSystem::new());
+        let shutdown = system.lock().unwrap().coordinated_shutdown();
+
+        let done = Arc::new(Mutex::new(false));
+        let task_system = system.clone();
+        let task_done = done.clone();
+        shutdown.lock().unwrap().add_task(PHASE_FLUSH, "lock-system", move || {
+            task_system.lock().unwrap().dead_letters_count();
+            *task_done.lock().unwrap() = true;
+        });
+        shutdown.lock().unwrap().set_phase_timeout(PHASE_FLUSH, Duration::from_secs(3));
+
+        let start = Instant::now();
+        let mut completion = {
+            let mut system = system.lock().unwrap();
+            let completion = system.terminate_async();
+
+            // Repeated call returns future of the already started shutdown
+            system.terminate_async();
+            completion
+        };
+
+        assert!(completion.ready(Duration::from_secs(5)));
+        assert!(*done.lock().unwrap());
+        assert!(start.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn root_is_terminated_with_system_shutdown_reason() {
+        let mut system = LocalActorSystem::new();
+        let mut observer = LocalActorSystem::new();
+
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let listener = observer.actor_of(Props::new(tsafe!(Listener { paths: paths.clone() })), None);
+        system.event_stream().subscribe::<Terminated>(&listener);
+        system.actor_of(Props::new(tsafe!(Listener { paths: Arc::new(Mutex::new(Vec::new())) })), Some("child"));
+
+        system.terminate();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while paths.lock().unwrap().len() < 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(*paths.lock().unwrap(), vec!["/root/child", "/root"]);
+
+        observer.terminate();
+    }
+}
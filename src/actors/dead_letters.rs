@@ -1,19 +1,111 @@
 //! Crematorium for undelivered messages
 //!
-//! All messages enqueued to this mailbox will be logged and destroyed.
+//! All messages enqueued to this mailbox will be logged and destroyed. For each message a
+//! DeadLetter event is published to the event stream of the actor system. Events is published from
+//! the separate thread, because the mailbox is called under locks of the actor cell and the
+//! dispatcher, and subscriber may be served by the same dispatcher. This thread is one per actor
+//! system and is shared by all dead letters mailboxes of the system.
 //!
 use crate::actors::mailbox::Mailbox;
 use crate::actors::envelope::Envelope;
 use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::event_stream::EventStream;
+use crate::actors::message::Message;
+use crate::actors::actor_path::ActorPath;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::thread;
+
+/// Maximum count of dead letters which is logged within the log window
+const LOG_LIMIT: u32 = 10;
+
+/// Duration of the log window
+const LOG_WINDOW: Duration = Duration::from_secs(1);
+
+/// Event which is published to the event stream for each undelivered message
+pub struct DeadLetter {
+
+    /// Undelivered message
+    pub message: Message,
+
+    /// Who send this message. None if message was sent from outside of the actor system.
+    pub sender: Option<ActorRef>,
+
+    /// Who must receive this message
+    pub recipient: ActorRef
+}
 
 pub struct DeadLetters {
-    is_planned: bool
+    is_planned: bool,
+
+    /// Path of the dead letters actor, messages sent from him is considered as sent from outside
+    path: ActorPath,
+
+    /// Channel to the thread which publishes events to the event stream
+    publisher: Sender<DeadLetter>,
+
+    /// Total count of the dead letters, shared with the actor system
+    count: Arc<AtomicUsize>,
+
+    /// Start of the current log window
+    window_start: Instant,
+
+    /// Count of the dead letters logged within the current window
+    logged: u32,
+
+    /// Count of the dead letters which was not logged within the current window
+    suppressed: u32
 }
 
 impl DeadLetters {
-    pub fn new() -> DeadLetters {
+
+    /// Creates mailbox which sends events through the publisher of the actor system
+    pub fn new(path: ActorPath, publisher: Sender<DeadLetter>, count: Arc<AtomicUsize>) -> DeadLetters {
         DeadLetters {
-            is_planned: true // It's always planned to prevent it's execution
+            is_planned: true, // It's always planned to prevent it's execution
+            path,
+            publisher,
+            count,
+            window_start: Instant::now(),
+            logged: 0,
+            suppressed: 0
+        }
+    }
+
+    /// Starts thread which publishes DeadLetter events to the event stream. Called once by the
+    /// actor system. Thread is exited when the actor system and all his mailboxes is dropped.
+    pub fn spawn_publisher(event_stream: EventStream) -> Sender<DeadLetter> {
+        let (publisher, receiver) = mpsc::channel::<DeadLetter>();
+
+        thread::spawn(move || {
+            for dead_letter in receiver.iter() {
+                event_stream.publish(msg!(dead_letter));
+            }
+        });
+
+        publisher
+    }
+
+    /// Is the dead letter must be logged. Only LOG_LIMIT records is logged within the log window,
+    /// count of others is reported at the start of the next window.
+    fn log_allowed(self: &mut Self) -> bool {
+        if self.window_start.elapsed() >= LOG_WINDOW {
+            if self.suppressed > 0 {
+                info!("{} dead letters was not logged in the last {:?}", self.suppressed, self.window_start.elapsed());
+            }
+            self.window_start = Instant::now();
+            self.logged = 0;
+            self.suppressed = 0;
+        }
+
+        if self.logged < LOG_LIMIT {
+            self.logged = self.logged + 1;
+            true
+        } else {
+            self.suppressed = self.suppressed + 1;
+            false
         }
     }
 }
@@ -30,22 +122,41 @@ impl Mailbox for DeadLetters {
         true
     }
 
-    /// Constructs a beautiful message and print it
+    /// Logs the message and publish DeadLetter event with it
     fn enqueue(self: &mut Self, envelope: Envelope) {
-        let mut actor_name = "outside".to_string();
+        self.count.fetch_add(1, Ordering::Relaxed);
+
+        let sender = match envelope.sender {
+            Some(sender) => {
+                if sender.path() == self.path {
+                    None
+                } else {
+                    Some(sender)
+                }
+            },
+            None => None
+        };
 
-        if envelope.sender.is_some() {
-            let sender =  &mut envelope.sender.as_ref().unwrap();
-            let sender_path = {
-                sender.path().name.clone()
+        if self.log_allowed() {
+            let actor_name = match sender.as_ref() {
+                Some(sender) => sender.to_string(),
+                None => "outside".to_string()
             };
-            if sender_path != "deadLetters" {
-                actor_name = sender.to_string();
-            }
-            //
+            info!("DeadLetter receive message of type '{}' from '{}' to '{}'", envelope.message.type_name(), actor_name, envelope.receiver);
         }
 
-        println!("DeadLetter receive some message ... from '{}' to '{}'", actor_name, envelope.receiver) //
+        // Dead letters of the DeadLetter events is not published for prevent of infinite loop
+        if envelope.message.get().is::<DeadLetter>() {
+            return;
+        }
+
+        let dead_letter = DeadLetter {
+            message: envelope.message,
+            sender,
+            recipient: envelope.receiver
+        };
+
+        let _ = self.publisher.send(dead_letter);
     }
 
     /// Oops! This mailbox does not contains the queue
@@ -58,6 +169,57 @@ impl Mailbox for DeadLetters {
         false
     }
 
+    fn size(self: &Self) -> usize {
+        0
+    }
+
     /// Do nothing
     fn clean_up(self: &mut Self, _sender: ActorRef, _dead_letters: ActorRef) {}
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::default_dispatcher::DefaultDispatcher;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    struct Listener {
+        types: Arc<Mutex<Vec<&'static str>>>
+    }
+
+    impl Actor for Listener {
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<DeadLetter>() {
+                Some(dead_letter) => self.types.lock().unwrap().push(dead_letter.message.type_name()),
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn dead_letters_is_published_after_replacing_of_default_dispatcher() {
+        let mut system = LocalActorSystem::new();
+        system.add_dispatcher("default", tsafe!(DefaultDispatcher::new(2)));
+
+        let types = Arc::new(Mutex::new(Vec::new()));
+        let listener = system.actor_of(Props::new(tsafe!(Listener { types: types.clone() })), None);
+        system.event_stream().subscribe::<DeadLetter>(&listener);
+
+        let mut dead_letters = system.dead_letters();
+        dead_letters.tell(msg!(5 as u32), None);
+        dead_letters.tell(msg!(String::from("x")), None);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while types.lock().unwrap().len() < 2 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(*types.lock().unwrap(), vec!["u32", "alloc::string::String"]);
+        assert_eq!(system.dead_letters_count(), 2);
+
+        system.terminate();
+    }
+}
@@ -1,21 +1,35 @@
 //! Actor dispatcher with strategy - thread pool and single actor / single thread in pool
-use crate::executors::thread_pinned_executor::{ThreadPinnedExecutor, DistributionStrategy, TaskOptions};
+use crate::executors::thread_pinned_executor::{ThreadPinnedExecutor, DistributionStrategy, TaskOptions, ResizePolicy};
 use crate::executors::executor::{Executor,ExecutorTask};
 use crate::actors::dispatcher::Dispatcher;
 use crate::actors::actor_cell::ActorCell;
+use crate::actors::watcher::StopReason;
 use crate::actors::local_actor_ref::LocalActorRef;
 use crate::actors::abstract_actor_ref::AbstractActorRef;
 use crate::actors::actor_context::ActorContext;
 use crate::actors::envelope::Envelope;
 use crate::actors::mailbox::Mailbox;
 use crate::actors::actor::{Actor, PoisonPill};
+use crate::actors::supervision::SiblingFailed;
+use crate::actors::unhandled_message::UnhandledMessage;
 use crate::actors::message::Message;
+use crate::actors::error::Error;
 use crate::common::tsafe::TSafe;
+use std::panic::{self, AssertUnwindSafe};
 use std::any::Any;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Default count of messages processed from a mailbox in one run
+pub const DEFAULT_THROUGHPUT: usize = 10;
 
 pub struct DefaultDispatcher {
     executor: ThreadPinnedExecutor,
-    rounds: usize
+    rounds: usize,
+
+    /// Maximum count of messages which will be processed from a mailbox, before the thread will
+    /// be yielded to other actors. May be changed at runtime.
+    throughput: Arc<AtomicUsize>
 }
 
 impl DefaultDispatcher {
@@ -26,86 +40,183 @@ impl DefaultDispatcher {
             .run();
         DefaultDispatcher {
             executor,
-            rounds: 0
+            rounds: 0,
+            throughput: Arc::new(AtomicUsize::new(DEFAULT_THROUGHPUT))
         }
     }
 
-    pub fn invoke(mailbox: &TSafe<Mailbox + Send>, actor: &TSafe<Actor + Send>, cell: &TSafe<ActorCell>) {
-        let envelope = {
+    /// Enqueues envelope to the mailbox and, if the mailbox is not planned yet, plans his
+    /// processing on the executor with the actor's thread as preferred. Used by all dispatchers
+    /// which is based on the message processing of the default dispatcher.
+    pub fn plan(executor: &mut Executor, throughput: &Arc<AtomicUsize>, cell: TSafe<ActorCell>, bid: usize, mailbox: TSafe<Mailbox + Send>, envelope: Envelope) {
+        let mut mailbox_u = mailbox.lock().unwrap();
+        mailbox_u.enqueue(envelope);
+        if !mailbox_u.is_planned() {
+            mailbox_u.set_planned(true);
+
+            let mailbox = mailbox.clone();
+            let throughput = throughput.clone();
+            let f = Box::new(move || {
+                DefaultDispatcher::invoke(&mailbox, &cell, &throughput)
+            });
+
+            executor.execute(f,  Some( Box::new(TaskOptions { thread_id: Some(bid) } )))
+        }
+    }
+
+    /// Processes messages from the mailbox, but no more than throughput value. If after that the
+    /// mailbox still have messages, next run is planned on the same thread. Otherwise, mailbox is
+    /// marked as not planned.
+    pub fn invoke(mailbox: &TSafe<Mailbox + Send>, cell: &TSafe<ActorCell>, throughput: &Arc<AtomicUsize>) {
+        let limit = throughput.load(Ordering::Relaxed);
+        let mut processed = 0;
+
+        while processed < limit {
+            let envelope = {
+                let mut mailbox = mailbox.lock().unwrap();
+                if mailbox.has_messages() {
+                    Some(mailbox.dequeue())
+                } else {
+                    None
+                }
+            };
+
+            match envelope {
+                Some(envelope) => DefaultDispatcher::invoke_envelope(envelope, mailbox, cell),
+                None => break
+            }
+
+            processed = processed + 1;
+        }
+
+        let has_messages = {
             let mut mailbox = mailbox.lock().unwrap();
             if mailbox.has_messages() {
-                Some(mailbox.dequeue())
+                true
             } else {
-                None
+                mailbox.set_planned(false);
+                false
             }
         };
 
-        if envelope.is_some() {
-            let envelope = envelope.unwrap();
+        if has_messages {
+            DefaultDispatcher::reschedule(mailbox.clone(), cell.clone(), throughput.clone());
+        }
+    }
 
-            let sender: Box<AbstractActorRef + Send> = {
-                if envelope.sender.is_some() {
-                    envelope.sender.unwrap()
-                } else {
-                    let mut system = envelope.system.lock().unwrap();
-                    let dead_letters = system.dead_letters();
-                    dead_letters
-                }
-            };
+    /// Plans next run of the mailbox processing on the thread of the actor. This is performed
+    /// through the cell, because dispatcher is not locked at this moment.
+    fn reschedule(mailbox: TSafe<Mailbox + Send>, cell: TSafe<ActorCell>, throughput: Arc<AtomicUsize>) {
+        let (dispatcher, bid) = {
+            let cell = cell.lock().unwrap();
+            (cell.dispatcher.clone(), cell.bid)
+        };
+
+        let f = Box::new(move || {
+            DefaultDispatcher::invoke(&mailbox, &cell, &throughput)
+        });
 
+        dispatcher.lock().unwrap().execute(f, Some( Box::new(TaskOptions { thread_id: Some(bid) } )));
+    }
 
-            let msg = envelope.message;
+    /// Passes single message to the actor. Supervision commands is processed here and is not
+    /// passed to the actor.
+    pub fn invoke_envelope(envelope: Envelope, mailbox: &TSafe<Mailbox + Send>, cell: &TSafe<ActorCell>) {
+        let self_ = envelope.receiver.clone();
+        DefaultDispatcher::invoke_envelope_as(envelope, mailbox, cell, self_)
+    }
 
-            let handled = {
-                let im = {
-                    let mut actor = actor.lock().unwrap();
-                    let ctx = ActorContext::new(
-                        sender.clone(),
-                        envelope.receiver.clone(),
-                        envelope.system.clone(),
-                        cell.clone());
-                    actor.receive(msg.clone(), ctx)
+    /// Same as invoke_envelope, but the actor sees the specified reference as his own in the
+    /// context. Used by dispatchers where message may be processed not by the actor to which it
+    /// was sent.
+    pub fn invoke_envelope_as(envelope: Envelope, mailbox: &TSafe<Mailbox + Send>, cell: &TSafe<ActorCell>, self_: Box<AbstractActorRef + Send>) {
+        let command = envelope.message.get().downcast_ref::<SiblingFailed>()
+            .map(|c| (c.strategy.clone(), c.err.clone()));
+        if let Some((strategy, err)) = command {
+            ActorCell::supervise_sibling(cell, strategy, err);
+            return;
+        }
+
+        let sender: Box<AbstractActorRef + Send> = {
+            if envelope.sender.is_some() {
+                envelope.sender.unwrap()
+            } else {
+                let mut system = envelope.system.lock().unwrap();
+                let dead_letters = system.dead_letters();
+                dead_letters
+            }
+        };
+
+
+        let msg = envelope.message;
+
+        let handled = {
+            let im = {
+                // Actor is taken from the cell, because he may be replaced at restart
+                let (behavior, actor) = {
+                    let cell = cell.lock().unwrap();
+                    (cell.current_behavior(), cell.actor.clone())
                 };
+                let mut actor = actor.lock().unwrap();
+                let ctx = ActorContext::new(
+                    sender.clone(),
+                    self_,
+                    envelope.system.clone(),
+                    cell.clone());
 
-                if im.is_ok() {
-                    im.ok().unwrap()
-                } else {
-                    let f = cell.lock().unwrap().fail(im.err().unwrap(), cell.clone());
-                    f();
-                    true
+                // Panic is caught while the actor and the behavior is still locked, so their mutexes will
+                // not be poisoned. Panic is passed to the supervision as usual error.
+                let mut behavior = behavior.as_ref().map(|b| b.lock().unwrap());
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    match behavior.as_mut() {
+                        Some(behavior) => (&mut **behavior)(msg.clone(), ctx),
+                        None => actor.receive(msg.clone(), ctx)
+                    }
+                }));
+
+                match result {
+                    Ok(im) => im,
+                    Err(payload) => Err(Error::from_panic(payload))
                 }
             };
 
-            if !handled {
-                let handled2 = DefaultDispatcher::internal_receive(mailbox, msg.clone(), cell);
-                if !handled2 {
-                    let mut dead_letters = {
-                        let mut system = envelope.system.lock().unwrap();
-                        let dead_letters = system.dead_letters();
-                        dead_letters
-                    };
-                    let dc = dead_letters.cell();
-                    let mut dc = dc.lock().unwrap();
-                    dc.send(&dead_letters.cell(), msg, Some(sender), envelope.receiver );
-                    println!("xxx");
-                }
+            if im.is_ok() {
+                im.ok().unwrap()
+            } else {
+                let f = cell.lock().unwrap().fail(im.err().unwrap(), Some(msg.clone()), cell.clone());
+                f();
+                true
             }
-        }
+        };
 
-        mailbox.lock().unwrap().set_planned(false);
+        if !handled {
+            let handled2 = DefaultDispatcher::internal_receive(mailbox, msg.clone(), cell);
+            if !handled2 {
+                UnhandledMessage::publish(&envelope.system, msg, sender, envelope.receiver);
+            }
+        }
     }
 
     pub fn internal_receive(mailbox: &TSafe<Mailbox + Send>, msg: Message, cell: &TSafe<ActorCell>) -> bool {
 
         if let Some(PoisonPill {}) = msg.get().downcast_ref::<PoisonPill>() {
+            let reason;
             {
                 let mut cell_u = cell.lock().unwrap();
+                // Actor stopped through the stop call is suspended before the PoisonPill is sent to him
+                reason = if cell_u.suspended { StopReason::Normal } else { StopReason::PoisonPill };
                 cell_u.suspend();
-                let dead_letters = cell_u.system.lock().unwrap().dead_letters();
-                mailbox.lock().unwrap().clean_up(Box::new(LocalActorRef::new(cell.clone(), cell_u.path.clone())), dead_letters);
+
+                // Shared mailbox is not cleaned, because messages from it will be processed by
+                // other actors of the team
+                let shared = cell_u.dispatcher.lock().unwrap().shared_mailbox().is_some();
+                if !shared {
+                    let dead_letters = cell_u.system.lock().unwrap().dead_letters();
+                    mailbox.lock().unwrap().clean_up(Box::new(LocalActorRef::new(cell.clone(), cell_u.path.clone())), dead_letters);
+                }
             }
 
-            let f = cell.lock().unwrap().stop(cell.clone());
+            let f = cell.lock().unwrap().stop_with_reason(cell.clone(), Some(reason));
             f();
         } else {
             return false
@@ -129,23 +240,14 @@ impl Executor for DefaultDispatcher {
 
 impl Dispatcher for DefaultDispatcher {
 
-    fn dispatch(self: &mut Self, cell: TSafe<ActorCell>, bid: usize, mailbox: TSafe<Mailbox + Send>, actor: TSafe<Actor + Send>, envelope: Envelope) {
-        let mut mailbox_u = mailbox.lock().unwrap();
-        mailbox_u.enqueue(envelope);
-        //if !mailbox_u.is_planned() {
-            //mailbox_u.set_planned(true);
-
-            let mailbox = mailbox.clone();
-            let f = Box::new(move || {
-                DefaultDispatcher::invoke(&mailbox, &actor, &cell)
-            });
-
-            self.execute(f,  Some( Box::new(TaskOptions { thread_id: Some(bid) } )))
-        //}
+    /// Actor is not used, because at the processing it's taken from the cell
+    fn dispatch(self: &mut Self, cell: TSafe<ActorCell>, bid: usize, mailbox: TSafe<Mailbox + Send>, _actor: TSafe<Actor + Send>, envelope: Envelope) {
+        let throughput = self.throughput.clone();
+        DefaultDispatcher::plan(&mut self.executor, &throughput, cell, bid, mailbox, envelope);
     }
 
     fn obtain_bid(self: &mut Self) -> usize {
-        if self.rounds == self.executor.get_threads_count() - 1 {
+        if self.rounds >= self.executor.get_threads_count() - 1 {
             self.rounds = 0;
         } else {
             self.rounds = self.rounds + 1;
@@ -153,4 +255,148 @@ impl Dispatcher for DefaultDispatcher {
 
         self.rounds
     }
-}
\ No newline at end of file
+
+    /// Panics if throughput is zero, because mailbox with messages will be rescheduled infinitely
+    fn set_throughput(self: &mut Self, throughput: usize) {
+        if throughput == 0 {
+            panic!("Throughput of the dispatcher must be greater than zero");
+        }
+        self.throughput.store(throughput, Ordering::Relaxed);
+    }
+
+    fn set_threads_count(self: &mut Self, count: usize) {
+        self.executor.resize(count);
+    }
+
+    fn set_resize_policy(self: &mut Self, policy: Option<ResizePolicy>) {
+        self.executor.update_resize_policy(policy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::default_dispatcher::DefaultDispatcher;
+    use crate::actors::dispatcher::Dispatcher;
+    use crate::actors::error::PanicError;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    struct Recorder {
+        log: Arc<Mutex<Vec<u32>>>
+    }
+
+    impl Actor for Recorder {
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<u32>() {
+                Some(v) => self.log.lock().unwrap().push(*v),
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    /// Panics on the "panic" message, counts numbers and records failures
+    struct Panicker {
+        count: u32,
+        log: Arc<Mutex<Vec<String>>>
+    }
+
+    impl Actor for Panicker {
+        fn pre_fail(&mut self, _ctx: ActorContext, err: Error, strategy: SupervisionStrategy) {
+            let message = err.get().downcast_ref::<PanicError>().map(|e| e.message.clone());
+            self.log.lock().unwrap().push(format!("fail {:?} {:?}", message, strategy));
+        }
+
+        fn post_restart(&mut self, _ctx: ActorContext) {
+            self.log.lock().unwrap().push(String::from("restarted"));
+        }
+
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            let msg = msg.get();
+            if let Some(&"panic") = msg.downcast_ref::<&str>() {
+                panic!("boom");
+            }
+            match msg.downcast_ref::<u32>() {
+                Some(_) => {
+                    self.count = self.count + 1;
+                    self.log.lock().unwrap().push(format!("count {}", self.count));
+                },
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    /// Sends number, panic and number again to the actor on the specified dispatcher
+    fn panic_in_receive(dispatcher: &str, strategy: SupervisionStrategy, len: usize) -> Vec<String> {
+        let mut system = LocalActorSystem::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let props = Props::new(tsafe!(Panicker { count: 0, log: log.clone() }))
+            .with_dispatcher(dispatcher)
+            .with_supervision_strategy(strategy);
+        let mut actor = system.actor_of(props, None);
+
+        actor.tell(msg!(1 as u32), None);
+        actor.tell(msg!("panic"), None);
+        actor.tell(msg!(1 as u32), None);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < len && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(Duration::from_millis(50));
+
+        // Mutex of the actor is not poisoned by the panic
+        let cell = actor.cell();
+        assert!(!cell.lock().unwrap().actor.is_poisoned());
+
+        system.terminate();
+        let log = log.lock().unwrap().clone();
+        log
+    }
+
+    fn wait_len(log: &Arc<Mutex<Vec<u32>>>, len: usize) {
+        let start = Instant::now();
+        while log.lock().unwrap().len() < len && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn all_messages_is_processed_in_order_with_any_throughput() {
+        for throughput in &[1, 3, 100] {
+            let mut system = LocalActorSystem::new();
+            system.get_dispatcher("default").lock().unwrap().set_throughput(*throughput);
+
+            let log = Arc::new(Mutex::new(Vec::new()));
+            let mut actor = system.actor_of(Props::new(tsafe!(Recorder { log: log.clone() })), None);
+            for i in 0..50 {
+                actor.tell(msg!(i as u32), None);
+            }
+
+            wait_len(&log, 50);
+            assert_eq!(*log.lock().unwrap(), (0..50).collect::<Vec<u32>>());
+            system.terminate();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Throughput of the dispatcher must be greater than zero")]
+    fn zero_throughput_is_rejected() {
+        let mut dispatcher = DefaultDispatcher::new(1);
+        dispatcher.set_throughput(0);
+    }
+    #[test]
+    fn panic_in_receive_is_resumed_by_supervision() {
+        let log = panic_in_receive("default", SupervisionStrategy::Resume, 3);
+        assert_eq!(log, vec!["count 1", "fail Some(\"boom\") Resume", "count 2"]);
+    }
+
+    #[test]
+    fn panic_in_receive_restarts_actor_by_supervision() {
+        let log = panic_in_receive("default", SupervisionStrategy::Restart, 4);
+        assert_eq!(log, vec!["count 1", "fail Some(\"boom\") Restart", "restarted", "count 2"]);
+    }
+}
@@ -4,6 +4,7 @@ use crate::actors::envelope::Envelope;
 use crate::actors::mailbox::Mailbox;
 use crate::actors::actor::Actor;
 use crate::executors::executor::Executor;
+use crate::executors::thread_pinned_executor::ResizePolicy;
 use crate::common::tsafe::TSafe;
 
 pub trait Dispatcher: Executor  {
@@ -15,4 +16,31 @@ pub trait Dispatcher: Executor  {
                 envelope: Envelope);
 
     fn obtain_bid(self: &mut Self) -> usize;
+
+    /// Sets maximum count of messages which may be processed from a mailbox in one run. May be
+    /// called at runtime. Must be greater than zero. Dispatchers without batch processing ignores
+    /// this value.
+    fn set_throughput(self: &mut Self, _throughput: usize) {}
+
+    /// Changes count of threads of the dispatcher at runtime. Actors pinned to the removed threads
+    /// is moved to the rest threads. Dispatchers with fixed threads count ignores this value.
+    fn set_threads_count(self: &mut Self, _count: usize) {}
+
+    /// Sets policy of automatically threads count changing based on the load of the dispatcher.
+    /// None value disables automatically changing. Dispatchers with fixed threads count ignores
+    /// this policy.
+    fn set_resize_policy(self: &mut Self, _policy: Option<ResizePolicy>) {}
+
+    /// Returns mailbox which must be used by all actors of this dispatcher, instead of the mailbox
+    /// specified in the props. Dispatchers which does not share the mailbox returns None.
+    fn shared_mailbox(self: &mut Self) -> Option<TSafe<Mailbox + Send>> {
+        None
+    }
+
+    /// Calls when actor which uses this dispatcher was started. Allows to dispatcher track his
+    /// actors.
+    fn attach(self: &mut Self, _cell: TSafe<ActorCell>, _actor: TSafe<Actor + Send>) {}
+
+    /// Calls when actor which uses this dispatcher was stopped
+    fn detach(self: &mut Self, _cell: &TSafe<ActorCell>) {}
 }
\ No newline at end of file
@@ -5,7 +5,7 @@
 //! unlimited number of times.
 use crate::common::tsafe::TSafe;
 use std::any::Any;
-use std::sync::{MutexGuard};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 /// Wraps any data type to the message wrapper
 ///
@@ -27,6 +27,13 @@ macro_rules! err {
 }
 
 
+/// Error which is created from the panic occurred in the message handler
+pub struct PanicError {
+
+    /// Message of the panic. If panic was called not with string, message is empty.
+    pub message: String
+}
+
 pub struct Error {
 
     /// Wrapped data
@@ -40,10 +47,28 @@ impl Error {
         }
     }
 
+    /// Creates error from the payload of the caught panic
+    pub fn from_panic(payload: Box<Any + Send>) -> Error {
+        let message = if let Some(m) = payload.downcast_ref::<&str>() {
+            String::from(*m)
+        } else if let Some(m) = payload.downcast_ref::<String>() {
+            m.clone()
+        } else {
+            String::new()
+        };
+
+        Error::new(Arc::new(Mutex::new(PanicError { message })))
+    }
+
     /// Returns MutexGuard of an inner error
     pub fn get(&self) -> MutexGuard<Any + Send> {
         self.inner.lock().unwrap()
     }
+
+    /// Checks whether an inner error is of the specified type. Useful in the supervision deciders.
+    pub fn is<T: Any>(&self) -> bool {
+        self.inner.lock().unwrap().is::<T>()
+    }
 }
 
 impl Clone for Error {
@@ -0,0 +1,111 @@
+//! System-wide event stream
+//!
+//! Publish/subscribe bus of the actor system. Actors subscribes to a message type and receives
+//! all published instances of this type. System itself publishes here lifecycle events of all
+//! actors - Terminated, Failed and Restarted. Subscriber is automatically unsubscribed from all
+//! types when he is terminated.
+//!
+//! # Example
+//!
+//! ```ignore
+//! ctx.system().event_stream().subscribe::<Terminated>(&ctx.self_);
+//!
+//! system.event_stream().publish(msg!(ConfigChanged {}));
+//! ```
+
+use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::message::Message;
+use crate::common::tsafe::TSafe;
+use std::collections::HashMap;
+use std::any::{Any, TypeId};
+use std::sync::{Arc, Mutex};
+
+/// Event stream is cloned by the actor system for each user. All clones shares the same list of
+/// subscribers.
+#[derive(Clone)]
+pub struct EventStream {
+
+    /// Subscribers grouped by the type of messages
+    subscribers: TSafe<HashMap<TypeId, Vec<ActorRef>>>
+}
+
+impl EventStream {
+    pub fn new() -> EventStream {
+        EventStream {
+            subscribers: tsafe!(HashMap::new())
+        }
+    }
+
+    /// Subscribes the actor to the messages of the specified type
+    pub fn subscribe<T: Any>(&self, subscriber: &ActorRef) {
+        self.subscribe_type(TypeId::of::<T>(), subscriber)
+    }
+
+    /// Subscribes the actor to the messages with the specified type id. Repeated subscription to
+    /// the same type is ignored.
+    pub fn subscribe_type(&self, type_id: TypeId, subscriber: &ActorRef) {
+        let mut all = self.subscribers.lock().unwrap();
+        let subscribers = all.entry(type_id).or_insert(Vec::new());
+        let path = subscriber.path();
+        if !subscribers.iter().any(|s| s.path() == path) {
+            subscribers.push((*subscriber).clone());
+        }
+    }
+
+    /// Unsubscribes the actor from the messages of the specified type
+    pub fn unsubscribe<T: Any>(&self, subscriber: &ActorRef) {
+        self.unsubscribe_type(TypeId::of::<T>(), subscriber)
+    }
+
+    /// Unsubscribes the actor from the messages with the specified type id
+    pub fn unsubscribe_type(&self, type_id: TypeId, subscriber: &ActorRef) {
+        let path = subscriber.path();
+        let mut all = self.subscribers.lock().unwrap();
+        let empty = match all.get_mut(&type_id) {
+            Some(subscribers) => {
+                subscribers.retain(|s| s.path() != path);
+                subscribers.len() == 0
+            },
+            None => false
+        };
+
+        if empty {
+            all.remove(&type_id);
+        }
+    }
+
+    /// Unsubscribes the actor from all messages types. Called by the watcher, when an actor is
+    /// terminated.
+    pub fn unsubscribe_all(&self, subscriber: &ActorRef) {
+        let path = subscriber.path();
+        let mut all = self.subscribers.lock().unwrap();
+        for (_, subscribers) in all.iter_mut() {
+            subscribers.retain(|s| s.path() != path);
+        }
+        all.retain(|_, subscribers| subscribers.len() > 0);
+    }
+
+    /// Is exists subscribers of the messages with specified type id. May be used for avoid of
+    /// creating of events which nobody listens.
+    pub fn has_subscribers(&self, type_id: TypeId) -> bool {
+        self.subscribers.lock().unwrap().contains_key(&type_id)
+    }
+
+    /// Sends the message to all actors subscribed to his type. Messages is sent after the list of
+    /// subscribers was released, so subscribers list may be safely changed at the message processing.
+    pub fn publish(&self, msg: Message) {
+        let type_id = {
+            let inner = msg.get();
+            (*inner).type_id()
+        };
+
+        let subscribers: Vec<ActorRef> = match self.subscribers.lock().unwrap().get(&type_id) {
+            Some(subscribers) => subscribers.iter().map(|s| (*s).clone()).collect(),
+            None => return
+        };
+
+        for mut subscriber in subscribers {
+            subscriber.tell(msg.clone(), None);
+        }
+    }
+}
@@ -49,7 +49,8 @@ impl AbstractActorRef for LocalActorRef {
      /// to ctx.sender field the actor context object. If sender was does not specified, ctx.sender
      /// will be filled with the deadLetter actor reference. Setting up None as sender reference is
      /// useful in case, when tell operation is
-     /// called from outside of the actor system.
+     /// called from outside of the actor system. If target actor has bounded mailbox with the
+     /// BlockSender overflow strategy, this call may block the caller while the mailbox is full.
      ///
      /// # Examples
      ///
@@ -60,8 +61,10 @@ impl AbstractActorRef for LocalActorRef {
         let cell_cloned = self.cell.clone();
         let path_cloned = self.path.clone();
         let toref = Box::new(LocalActorRef::new(cell_cloned, path_cloned));
-        let mut cell = self.cell.lock().unwrap();
-        cell.send(&self.cell, msg, rself.map_or(None, |v| Some((*v).clone())), toref)
+
+        // Messages for actors with the calling thread dispatcher is processed here, after the
+        // cell was released
+        ActorCell::send_and_run(&self.cell, msg, rself.map_or(None, |v| Some((*v).clone())), toref);
     }
 
     /// Call ask_timeout with default timeout
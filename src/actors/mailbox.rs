@@ -4,6 +4,18 @@
 //!
 use crate::actors::envelope::Envelope;
 use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::unbound_mailbox::UnboundMailbox;
+use crate::actors::bounded_mailbox::{BoundedMailbox, OverflowStrategy};
+use crate::actors::control_aware_mailbox::ControlAwareMailbox;
+use crate::common::tsafe::TSafe;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Condvar};
+use std::time::{Duration, Instant};
+use std::thread::{self, ThreadId};
+
+/// Constructor of mailboxes registered in the actor system
+pub type MailboxFactory = Box<Fn() -> TSafe<Mailbox + Send> + Send>;
+
 //TODO нужно переделать mailbox, так как блокироваться должно очередь внутри него а не сам объект. При текущем варинте невозможно реализация блокирующих ящиков.
 pub trait Mailbox {
 
@@ -26,4 +38,139 @@ pub trait Mailbox {
 
     /// Checks messages existing in the mailbox
     fn has_messages(self: &Self) -> bool;
+
+    /// Returns count of messages in the mailbox. Default implementation is for mailboxes which
+    /// can't count they messages, it returns only 1 or 0, based on the has_messages.
+    fn size(self: &Self) -> usize {
+        if self.has_messages() { 1 } else { 0 }
+    }
+
+    /// Returns messages which was rejected by the mailbox at enqueue, for example because of
+    /// overflow. Actor cell forwards this messages to the DeadLetters right after dispatching.
+    fn drain_rejected(self: &mut Self) -> Vec<Envelope> {
+        Vec::new()
+    }
+
+    /// Returns messages which does not fit to the full mailbox, but whose senders may wait for free
+    /// space. Such senders retries them after waiting on the gate, messages of others is dropped to
+    /// the DeadLetters.
+    fn drain_blocked(self: &mut Self) -> Vec<Envelope> {
+        Vec::new()
+    }
+
+    /// Returns gate on which a sender may wait for free space in the mailbox. Gate is used by
+    /// mailboxes which blocks senders at overflow.
+    fn gate(self: &Self) -> Option<MailboxGate> {
+        None
+    }
+}
+
+/// Current state of the mailbox, which is visible for blocked senders
+struct GateState {
+
+    /// Current size of the mailbox queue
+    size: usize,
+
+    /// Thread which processed messages from the mailbox last time
+    owner: Option<ThreadId>
+}
+
+/// Barrier which blocks senders while the mailbox is full. This object is passed outside of the
+/// actor cell and mailbox locks, so blocked sender does not prevent messages processing. Gate only
+/// says that the mailbox probably has free space, real check is performed by the mailbox at
+/// enqueue.
+#[derive(Clone)]
+pub struct MailboxGate {
+
+    /// State of the mailbox and notifier of its changes
+    state: Arc<(Mutex<GateState>, Condvar)>,
+
+    /// Maximum size of the mailbox queue
+    capacity: usize,
+
+    /// Maximum time of the sender blocking
+    timeout: Duration
+}
+
+impl MailboxGate {
+    pub fn new(capacity: usize, timeout: Duration) -> MailboxGate {
+        MailboxGate {
+            state: Arc::new((Mutex::new(GateState { size: 0, owner: None }), Condvar::new())),
+            capacity,
+            timeout
+        }
+    }
+
+    /// Maximum time of the sender blocking
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Publishes current size of the mailbox queue to the blocked senders. Called by the mailbox.
+    pub fn update_size(&self, size: usize) {
+        let (state, cvar) = &*self.state;
+        state.lock().unwrap().size = size;
+        cvar.notify_all();
+    }
+
+    /// Remembers the current thread as the thread which processes messages from the mailbox.
+    /// Called by the mailbox at dequeue.
+    pub fn set_owner(&self) {
+        let (state, _) = &*self.state;
+        state.lock().unwrap().owner = Some(thread::current().id());
+    }
+
+    /// Blocks the caller until the mailbox will have free space or the deadline will be reached.
+    /// Returns true if the mailbox have free space. If the mailbox is processed by the current
+    /// thread, false is returned immediately, because his space can't be freed while the caller is
+    /// blocked.
+    pub fn wait(&self, deadline: Instant) -> bool {
+        let (state, cvar) = &*self.state;
+        let state = state.lock().unwrap();
+        if state.owner == Some(thread::current().id()) {
+            return false;
+        }
+
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let (state, _) = cvar.wait_timeout_while(state, timeout, |s| s.size >= self.capacity).unwrap();
+
+        state.size < self.capacity
+    }
+}
+
+/// Type of the mailbox which will be created for an actor. Is specified through Props.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MailboxType {
+
+    /// Mailbox without queue size restrictions
+    Unbound,
+
+    /// Mailbox with specified capacity and overflow strategy
+    Bounded(usize, OverflowStrategy),
+
+    /// Mailbox with default set of control messages which is processed before others
+    ControlAware,
+
+    /// Mailbox created by the factory registered in the actor system under specified name
+    Named(String)
+}
+
+impl MailboxType {
+
+    /// Creates new mailbox instance of this type. Named mailboxes is searched in the specified
+    /// factories list. If factory does not found, it will cause to panic!
+    pub fn create(&self, factories: &HashMap<String, MailboxFactory>) -> TSafe<Mailbox + Send> {
+        match self {
+            MailboxType::Unbound => tsafe!(UnboundMailbox::new()),
+            MailboxType::Bounded(capacity, strategy) =>
+                tsafe!(BoundedMailbox::new(*capacity, strategy.clone())),
+            MailboxType::ControlAware => tsafe!(ControlAwareMailbox::new()),
+            MailboxType::Named(name) => {
+                match factories.get(name) {
+                    Some(factory) => factory(),
+                    None => panic!("Mailbox with name '{}' does not registered", name)
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
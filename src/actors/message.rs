@@ -4,7 +4,7 @@
 //! provides he safe shared between threads and concurrent access. This object may be cloned
 //! unlimited number of times.
 use crate::common::tsafe::TSafe;
-use std::any::Any;
+use std::any::{Any, type_name};
 use std::sync::{MutexGuard};
 
 /// Wraps any data type to the message wrapper
@@ -30,16 +30,25 @@ macro_rules! msg {
 pub struct Message {
 
     /// Wrapped data
-    pub inner: TSafe<Any + Send>
+    pub inner: TSafe<Any + Send>,
+
+    /// Name of the wrapped data type. Used only for logging.
+    type_name: &'static str
 }
 
 impl Message {
-    pub fn new(inner: TSafe<Any + Send>) -> Message {
+    pub fn new<T: Any + Send>(inner: TSafe<T>) -> Message {
         Message {
-            inner
+            inner,
+            type_name: type_name::<T>()
         }
     }
 
+    /// Returns name of the wrapped data type
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
     /// Returns MutexGuard of an inner message
     pub fn get(&self) -> MutexGuard<Any + Send> {
         self.inner.lock().unwrap()
@@ -49,7 +58,12 @@ impl Message {
 impl Clone for Message {
     fn clone(&self) -> Self {
         Message {
-            inner: self.inner.clone()
+            inner: self.inner.clone(),
+            type_name: self.type_name
         }
     }
-}
\ No newline at end of file
+}
+
+/// Marker of service messages which must be processed before any other messages, if the actor uses
+/// control aware mailbox. See ControlAwareMailbox for more details.
+pub trait ControlMessage {}
@@ -13,11 +13,15 @@
 //! 10. [More about dispatchers](#more-about-dispatchers)
 //! 11. [Timers](#timers)
 //! 12. [Stash](#stash)
-//! 13. [Watching](#watching)
-//! 14. [Ask](#ask)
-//! 15. [FSM](#fsm)
-//! 16. [Supervision](#supervision)
-//! 17. [Remoting](#remoting)
+//! 13. [Become/Unbecome](#becomeunbecome)
+//! 14. [Watching](#watching)
+//! 15. [Event stream](#event-stream)
+//! 16. [Ask](#ask)
+//! 17. [FSM](#fsm)
+//! 18. [Supervision](#supervision)
+//! 19. [Routing](#routing)
+//! 20. [Remoting](#remoting)
+//! 21. [Coordinated shutdown](#coordinated-shutdown)
 //!
 //!
 //! # Introduction
@@ -34,12 +38,14 @@
 //! * Watching
 //! * Various dispatchers realizations
 //! * FSM
+//! * Become/Unbecome
+//! * Custom mailboxes
+//! * Routers
 //!
 //! # Perspective features:
 //! Under this features already exists architectural basis, and their implementation is a question
 //! of my free time.
 //!
-//! * Mailboxes with user defined functional
 //! * Supervising
 //! * Distributed actor systems
 //!
@@ -64,6 +70,42 @@
 //!
 //!     * UnboundedMailbox - mailbox without any queue size restrictions. This mailbox is used by
 //! default at the time of actor creation, if other mailbox type, does not specified explicitly.
+//!     * BoundedMailbox - mailbox with restricted queue size. What to do with messages which does
+//! not fit to the queue, is determined by the overflow strategy - drop newest message, drop oldest
+//! message, drop new message to the DeadLetters or block the sender for some timeout. May be set
+//! up through Props:
+//!
+//! ```ignore
+//! let props = Props::new(tsafe!(SomeActor::new()))
+//!        .with_bounded_mailbox(1000, OverflowStrategy::DropOldest);
+//! ```
+//!
+//! * PriorityMailbox - mailbox which orders messages by the priority calculated by a user
+//! defined function. Messages with equal priority is processed in the order of their arrival.
+//! Since this mailbox requires the priority function, it must be registered in the actor system
+//! as described below.
+//!
+//! * ControlAwareMailbox - mailbox which processes control messages (PoisonPill, Terminated
+//! and other types marked with the ControlMessage trait and registered through with_control call)
+//! before all other messages. This permits to stop or notify an actor with predictable latency,
+//! even if his mailbox is flooded. With default set of control messages, it may be set up through
+//! Props:
+//!
+//! ```ignore
+//! let props = Props::new(tsafe!(SomeActor::new()))
+//!        .with_control_aware_mailbox();
+//! ```
+//!
+//! Besides this, you may implement your own mailbox based on the Mailbox trait. For use it, you
+//! need register it's factory in the actor system under some name, and after that specify this
+//! name in Props:
+//!
+//! ```ignore
+//! system.add_mailbox("my-mailbox", Box::new(|| tsafe!(MyMailbox::new())));
+//!
+//! let props = Props::new(tsafe!(SomeActor::new()))
+//!        .with_mailbox("my-mailbox");
+//! ```
 //!
 //! * Dispatcher - entity which process the messages. Message processing may be planned in it, and
 //! after that, dispatcher decides himself, how and when run his processing. Message processing is
@@ -78,6 +120,14 @@
 //! actor. Stops at the same time with serviced actor. Used for situations when actor needs to
 //! perform some heavy synchronous tasks such as working with a files or using a network. Blocking
 //! of message processing in this dispatcher have nothing impact to other actors in the system.
+//!     * WorkStealingDispatcher - dispatcher based on WorkStealingExecutor. Like the default
+//! dispatcher, each actor has preferred thread, but idle threads may take ready mailboxes from the
+//! queues of busy threads. Used for systems with uneven load between actors.
+//!     * BalancingDispatcher - dispatcher which shares one mailbox between all his actors. Message
+//! sent to any of this actors will be processed by the first idle actor. Used for pools of
+//! identical stateless workers.
+//!     * CallingThreadDispatcher - dispatcher without own threads, which processes messages
+//! synchronously on the thread which sent them. Used for deterministic testing of actors.
 //!
 //!  Runtime consists five object:
 //! * Actor - user defined structure implemented with the Actor trait. This object contains
@@ -223,6 +273,8 @@
 //! may be user to perform various operations.
 //! * pre_fail - Called before some supervising operation will be started. Read more about what it
 //! in the special chapter.
+//! * pre_restart - Called on the failed actor before restart. Receives occurred error and the
+//! message at which processing the error was occurred.
 //! * post_restart - Called after actor was restarted. This may occurs if some supervision action
 //! occurs. Read more about what it in the special chapter.
 //!
@@ -388,16 +440,45 @@
 //! must be delivered to the target actor and consumed. If this is not happens, and message not
 //! consumed be the receiver, this is an semantic error in the your program. Main task of the
 //! DeadLetters, is quite aggressive indicates to you, that this error type was occurs in your
-//! program. For this, DeadLetter log each received by him message to the stdout.
+//! program. For this, DeadLetter log each received by him message through the log crate with
+//! info level.
 //!
 //! ```text
 //! DeadLetter receive some message ... from 'ActorRef (/stdout_writer)' to 'ActorRef (/logger)'
 //! ```
 //!
-//! This feature is unable to disable be the ideological reasons. Because not processed messages is
-//! an error! In practical, this prints is very useful for actor system debug , when something goes
+//! In practical, this records is very useful for actor system debug , when something goes
 //! wrong and work improperly. 90% of errors in actor-based programs linked to the problem of not
-//! delivered messages and DeadLetters allows to you fastly determine bags.
+//! delivered messages and DeadLetters allows to you fastly determine bags. For prevent of log
+//! flooding, only 10 records per second is logged. Count of others is reported by the single
+//! record at the next second.
+//!
+//! For each undelivered message, DeadLetter event is published to the event stream. Event contains
+//! the message itself, his sender and recipient. Subscribe to this event, if you need to react on
+//! delivery failures, for example for send alerts from the production:
+//!
+//! ```ignore
+//! ctx.system().event_stream().subscribe::<DeadLetter>(&ctx.self_);
+//!
+//! ...
+//!
+//! fn receive(self: &mut Self, msg: Message, ctx: ActorContext) -> HandleResult {
+//!     match_downcast_ref!(msg.get(), {
+//!         m: DeadLetter => {
+//!             println!("Message to {} was not delivered", m.recipient)
+//!         },
+//!         _ => return Ok(false)
+//!     });
+//!
+//!     Ok(true)
+//! }
+//! ```
+//!
+//! Total count of dead letters from the start of the actor system may be obtained directly from it:
+//!
+//! ```ignore
+//! let count = system.dead_letters_count();
+//! ```
 //!
 //! Also, you may encounter with situation, when you may want to interact with DeadLetters directly.
 //! This is may be performed by obtaining the DeadLetter actor reference. This reference stored in
@@ -442,6 +523,86 @@
 //!
 //! ```
 //!
+//! ## Throughput
+//!
+//! Default dispatcher processes messages of an actor in batches. When mailbox receives a message,
+//! it is planned for execution only once. After that, dispatcher processes messages from this
+//! mailbox until it will be empty, but no more than throughput value. If mailbox still contains
+//! messages, next batch is planned after tasks of other actors pinned to the same thread. Bigger
+//! throughput value reduces scheduling overhead for actors with high message rate, but increases
+//! latency of other actors. By default throughput is 10. It may be changed at any time, but it
+//! must be greater than zero:
+//!
+//! ```ignore
+//! system.get_dispatcher("default").lock().unwrap().set_throughput(50);
+//! ```
+//!
+//! ## Threads count
+//!
+//! Count of threads of the default dispatcher may be changed at runtime. Actors which was pinned to
+//! the removed threads, will be moved to the rest threads. Messages of this actors will not be lost
+//! and will be processed in the same order.
+//!
+//! ```ignore
+//! system.get_dispatcher("default").lock().unwrap().set_threads_count(16);
+//! ```
+//!
+//! Also threads count may be changed automatically, based on the dispatcher load. For do it, you
+//! need to set resize policy. This policy defines the range of threads count and the thresholds of
+//! average count of planned tasks per thread, after which thread will be added or removed:
+//!
+//! ```ignore
+//! let policy = ResizePolicy::new(2, 16)
+//!     .set_grow_threshold(10)
+//!     .set_shrink_threshold(0)
+//!     .set_interval(Duration::from_millis(500));
+//!
+//! system.get_dispatcher("default").lock().unwrap().set_resize_policy(Some(policy));
+//! ```
+//!
+//! ## Work stealing
+//!
+//! With default dispatcher, actor is pinned to the one thread. If few heavy actors are pinned to
+//! the same thread, messages of other actors on this thread will wait, while rest of the threads may
+//! do nothing. WorkStealingDispatcher solves this problem. Each thread of this dispatcher has own
+//! queue of ready mailboxes, but if thread has no work, he takes mailboxes from the queues of other
+//! threads. Messages of the one actor is still processed sequentially. This dispatcher is not
+//! registered by default, you need to add it to the system:
+//!
+//! ```ignore
+//! system.add_dispatcher("stealing", tsafe!(WorkStealingDispatcher::new(8)));
+//!
+//! let props = Props::new(tsafe!(BasicActor::new()))
+//!     .with_dispatcher("stealing");
+//! ```
+//!
+//! ## Balancing dispatcher
+//!
+//! If you have a few identical stateless actors, which do the same work, you may join them to the
+//! team with the balancing dispatcher. All actors of the team uses the one mailbox, which is owned
+//! by the dispatcher. It does not matter to which actor of the team message was sent, it will be
+//! processed by the first idle actor. Each instance of the dispatcher serves only one team, so for
+//! each team you need to register separate dispatcher. Actor object must be created separately for
+//! each member of the team:
+//!
+//! ```ignore
+//! system.add_dispatcher("workers", tsafe!(BalancingDispatcher::new(4)));
+//!
+//! let mut workers = Vec::new();
+//! for _ in 0..4 {
+//!     let props = Props::new(tsafe!(Worker::new()))
+//!         .with_dispatcher("workers");
+//!     workers.push(system.actor_of(props, None));
+//! }
+//!
+//! // Will be processed by any idle worker
+//! workers[0].tell(msg!(Job {}), None);
+//! ```
+//!
+//! Mailbox specified in the props is ignored for actors of the team. PoisonPill is not placed to
+//! the shared mailbox, it's passed directly to the actor to which it was sent. So it stops only
+//! this actor, and messages from the shared mailbox is not dropped.
+//!
 //! ## Implementing custom dispatcher
 //! You can implement your own dispatcher type with some specific functionality. This is not magic
 //! action, because actor system dispatcher is a simple struct which implements the two traits -
@@ -566,6 +727,35 @@
 //! ```
 //! self.stash.stop();
 //! ```
+//!
+//! # Become/Unbecome
+//!
+//! Actor may change it's message handler at runtime. Behavior is a closure with the same signature
+//! as the receive function. After it will be installed, all subsequent messages will be passed to
+//! him instead of the receive function:
+//!
+//! ```ignore
+//! ctx.become_(move |msg, mut ctx| {
+//!     // Handle messages in the new manner
+//!     Ok(true)
+//! });
+//! ```
+//!
+//! Behaviors is stored in the stack inside the actor cell. Call of become_ replaces top of the
+//! stack. If you want to return to the previous behavior later, use stacked mode:
+//!
+//! ```ignore
+//! ctx.become_stacked(move |msg, mut ctx| {
+//!     // Handle some messages and when it need, return to the previous behavior
+//!     ctx.unbecome();
+//!     Ok(true)
+//! });
+//! ```
+//!
+//! When stack is empty, receive function is used as message handler. Behavior does not have access
+//! to the actor object, so if it need to share some data with it, data must be shared through
+//! TSafe. Stack is fully cleared when the actor is restarted.
+//!
 //! # Watching
 //!
 //! Each actor have special set of internal events. When event from this set occurs, it's passed
@@ -583,7 +773,37 @@
 //! types:
 //!
 //! * Terminated - occurs when watched actor is fully stopped (event occurs right after post_stop
-//! hook was called).
+//! hook was called). Event contains reference to the terminated actor and the reason of stop -
+//! Normal (stop call or stop of the parent), PoisonPill, Supervision or SystemShutdown. Restart
+//! of the actor does not cause this event.
+//! * Failed - occurs when watched actor is failed at a message processing. Contains the error and
+//! the supervision action which will be applied to the actor.
+//! * Restarted - occurs when watched actor was restarted by the supervision. Contains the error
+//! which caused the restart.
+//!
+//! Failed and Restarted events is sent only to actors subscribed through watch_lifecycle call.
+//! This call returns LifecycleWatchError, if the system is unable to deliver this events (for
+//! example RemoteActorSystem, which does not transfer them through the network):
+//!
+//! ```ignore
+//! ctx.system().watch_lifecycle(&ctx.self_, &self.target).ok().unwrap();
+//!
+//! ...
+//!
+//! fn receive(self: &mut Self, msg: Message, ctx: ActorContext) -> HandleResult {
+//!     match_downcast_ref!(msg.get(), {
+//!         m: Terminated => {
+//!             println!("Actor {} stopped with reason {:?}", m.actor, m.reason)
+//!         },
+//!         m: Failed => {
+//!             println!("Actor {} failed", m.actor)
+//!         },
+//!         _ => return Ok(false)
+//!     });
+//!
+//!     Ok(true)
+//! }
+//! ```
 //!
 //! Actor may unsubscribes from actor events in similar way, using function unwatch:
 //!
@@ -598,6 +818,44 @@
 //!
 //! You may see full example usage of watching in the 'examples/actors/watch' submodule.
 //!
+//! # Event stream
+//!
+//! Event stream is the system-wide publish/subscribe bus. Actor subscribes to some message type
+//! and after that receives all messages of this type, which is published to the stream by anyone.
+//! Subscription is performed by the TypeId of the message:
+//!
+//! ```ignore
+//! ctx.system().event_stream().subscribe::<ConfigChanged>(&ctx.self_);
+//! ```
+//!
+//! Published message is sent to the all subscribers of his type with sender None (deadLetters):
+//!
+//! ```ignore
+//! system.event_stream().publish(msg!(ConfigChanged { version: 2 }));
+//! ```
+//!
+//! Actor system itself publishes to the stream lifecycle events of all actors - Terminated,
+//! Failed and Restarted, and DeadLetter events for all undelivered messages. This allows to track
+//! all actors of the system without watching of each of them. If you does not need events more,
+//! you may unsubscribe from them:
+//!
+//! ```ignore
+//! ctx.system().event_stream().unsubscribe::<Terminated>(&ctx.self_);
+//! ```
+//!
+//! Unlike watching, you don't need to unsubscribe actor before he will be stopped. When actor is
+//! terminated, he is automatically unsubscribed from all types by the watcher.
+//!
+//! If the actor returns Ok(false) from the receive function, message is considered as unhandled.
+//! Such message is not dropped to the DeadLetters, instead of this the UnhandledMessage event is
+//! published to the event stream. Event contains the message, his sender and the recipient actor,
+//! so you may find where your actors protocols does not match. Also each unhandled message may be
+//! logged with warning level. This is disabled by default and may be enabled in the actor system:
+//!
+//! ```ignore
+//! system.set_log_unhandled(true);
+//! ```
+//!
 //! # Ask
 //!
 //! Ask call doing the same thing that tell call, but expects, that target actor respond back with
@@ -867,6 +1125,10 @@
 //! }
 //! ```
 //!
+//! Panic in the message handler is treated the same way as the returned error. Panic is caught by
+//! the dispatcher and converted to the Error with PanicError inside, which contains the panic
+//! message. So panicked actor does not breaks the dispatcher thread and is supervised as usual.
+//!
 //! This situation treated be system as signal that processed actor was faced with an insoluble
 //! error, and system must take some actions. This actions is called supervision operation and
 //! determined with supervision strategy. Exist four strategies:
@@ -874,7 +1136,7 @@
 //! * Resume - do nothing, ignore occurred error
 //! * Stop - stop the actor
 //! * Restart - restart the actor (stop and start)
-//! * Escalate - lift this error to the parent of the actor
+//! * Escalate - lift this error to the parent of the actor. Actor without parent is stopped
 //!
 //! All created actors has Restart strategy by default. Root Guardian and DeadLetters has Resume
 //! strategy. Strategy of a separate actor may be set up through Props object:
@@ -887,6 +1149,218 @@
 //! Before starts of the supervision operation, pre_fail hook of the failed actor will be called.
 //! This hook contains the actor context, occurred error and current supervision strategy.
 //!
+//! ### Restart
+//!
+//! Restart of the actor is performed in the next order. At first, pre_restart hook is called with
+//! the error and the message which caused the failure. Next, actor is stopped with calling of the
+//! post_stop hook. After that actor is started again with calling of the pre_start hook, and at
+//! the end, post_restart hook is called.
+//!
+//! If actor was created through Props::new, the same actor object is used after restart, so all
+//! his fields keeps values which they was have at the failure moment. For reset the actor state,
+//! props must be created with actor factory. In this case, at each restart, old actor object is
+//! replaced by the new one:
+//!
+//! ```ignore
+//! let props = Props::from_factory(|| tsafe!(SomeActor::new()));
+//! ```
+//!
+//! ### Supervisor strategy
+//!
+//! Strategy from the child props applies the same action to any error. If different errors needs
+//! different actions, parent may define supervisor strategy for his childs. This strategy contains
+//! decider - function which receives occurred error and returns action which must be applied:
+//!
+//! ```ignore
+//! let props = Props::new(tsafe!(SomeActor::new()))
+//!        .with_supervisor_strategy(SupervisorStrategy::one_for_one(|err| {
+//!             if err.is::<TimeoutError>() {
+//!                 SupervisionStrategy::Resume
+//!             } else if err.is::<CorruptedStateError>() {
+//!                 SupervisionStrategy::Restart
+//!             } else {
+//!                 SupervisionStrategy::Escalate
+//!             }
+//!        }));
+//! ```
+//!
+//! One-for-one strategy applies action only to the failed child. All-for-one strategy, created
+//! through SupervisorStrategy::all_for_one, applies Restart and Stop actions to the failed child
+//! and all his siblings. This is useful when childs is strongly depends on each other. Siblings
+//! is suspended right after the failure, so they do not receive new messages, and the action is
+//! applied to each of them after he processes messages which already is in his mailbox. If parent
+//! has supervisor strategy, supervision strategies of the childs props is ignored.
+//!
+//! ### Restart limit
+//!
+//! Actor which fails on each message, will be restarted forever. For avoid this, restart limit may
+//! be set up through props. If actor restarts more than specified count of times within the time
+//! window, instead of the next restart he will be stopped, or the error will be escalated:
+//!
+//! ```ignore
+//! let props = Props::new(tsafe!(SomeActor::new()))
+//!        .with_restart_limit(RestartLimit::new(3, Duration::from_secs(10))
+//!             .set_exceed_strategy(SupervisionStrategy::Escalate));
+//! ```
+//!
+//! Limit is checked for restarts decided by both the actor's own strategy and by the supervisor
+//! strategy of his parent. With all-for-one strategy, each restarted sibling checks his own limit.
+//!
+//! ### Backoff supervisor
+//!
+//! Actors which works with flaky resources, like a database or a device, often fails many times in
+//! a row. Immediate restart of such actor is useless. BackoffSupervisor creates the child from the
+//! specified props factory and, when the child fails or stops, recreates him after the delay. Delay
+//! grows exponentially from the min to the max backoff and is extended on random part:
+//!
+//! ```ignore
+//! let props = BackoffSupervisor::new(Duration::from_millis(100), Duration::from_secs(30))
+//!     .set_random_factor(0.2)
+//!     .set_buffer_size(100)
+//!     .props("storage", || Props::new(tsafe!(Storage::new())));
+//!
+//! let mut supervisor = system.actor_of(props, Some("storage_supervisor"));
+//! ```
+//!
+//! Messages sent to the supervisor is forwarded to the child. While child does not exists, they is
+//! buffered, or dropped to the dead letters if buffer is full.
+//!
+//! # Routing
+//!
+//! Router is a special actor, which does not process messages itself, but forwards they to the set
+//! of other actors - routees. Which routees will receive the message is defined by the routing
+//! logic. Original sender of the message is preserved, so routee may respond directly to him.
+//!
+//! ## Pool routers
+//!
+//! Pool router creates routees itself as his childs. Routees is created at router start from props
+//! returned by the factory function. Factory is called for each routee, so each of them has it's
+//! own actor object:
+//!
+//! ```ignore
+//! let props = Pool::round_robin(5)
+//!     .props(|| Props::new(tsafe!(Worker::new())));
+//!
+//! let mut router = system.actor_of(props, Some("workers"));
+//! router.tell(msg!(commands::Job {}), None);
+//! ```
+//!
+//! Exists next predefined routing logics:
+//!
+//! * round_robin - sends messages to routees one by one in the cycle
+//! * random - sends each message to the random routee
+//! * smallest_mailbox - sends each message to the routee with fewest messages in the mailbox. Remote
+//!   routees is selected only if there is no local routees, because their mailboxes is not visible
+//! * broadcast - sends each message to all routees
+//! * consistent_hashing - sends messages with the same key always to the same routee
+//! * scatter_gather_first_completed - sends each message to all routees and responds with the
+//! first answer
+//! * tail_chopping - sends each message to the random routee, and to the next random routees
+//! through the specified interval, while someone will not answer
+//!
+//! Also you may implement your own logic through the RoutingLogic trait and pass it to the Pool::new
+//! constructor.
+//!
+//! ## Group routers
+//!
+//! Group router does not create routees itself, but uses existed actors specified by their paths.
+//! Paths is resolved through actor_select at router start. Router watches resolved routees and
+//! removes terminated routees from the list. All routing logics presented for pools, except
+//! smallest_mailbox, is also available for groups:
+//!
+//! ```ignore
+//! let props = Group::round_robin(vec!["/root/worker1", "/root/worker2"])
+//!     .props();
+//!
+//! let mut router = system.actor_of(props, Some("workers"));
+//! ```
+//!
+//! By default paths is resolved in the router's actor system. For routing to the actors of a
+//! remote system, you need to set it as resolver. Remote actors is not watched across the network,
+//! so router with resolver periodically resolves paths again. Remote routees which is not resolved
+//! anymore, is removed from the routees list, and new resolved routees is added to it:
+//!
+//! ```ignore
+//! let props = Group::random(vec!["/root/worker1", "/root/worker2"])
+//!     .with_resolver(tsafe!(remote_system))
+//!     .with_resolve_interval(Duration::from_secs(5))
+//!     .props();
+//! ```
+//!
+//! Unlike pools, PoisonPill sent to the group router, stops only router itself, because routees is
+//! not owned by him.
+//!
+//! ## Consistent hashing
+//!
+//! This routing logic is used when messages related to the same entity, for example to the same
+//! customer, must be always processed by the same routee. Key of the message is extracted by the
+//! user defined function. Key may be any type which implements Hash trait. If function returns
+//! None, message will be dropped to the deadLetter.
+//!
+//! ```ignore
+//! let props = Pool::consistent_hashing(5, 100, |msg: &Message| {
+//!     msg.get().downcast_ref::<commands::Order>().map(|m| m.customer_id)
+//! }).props(|| Props::new(tsafe!(Worker::new())));
+//! ```
+//!
+//! Second argument is the count of virtual nodes. Each routee is placed to the hash ring this
+//! count of times, so more virtual nodes gives more even distribution of keys between routees.
+//! When routee is added or removed, only keys of this routee is moved to other routees. Keys of
+//! the rest routees stays in place.
+//!
+//! ## First completed
+//!
+//! Scatter-gather and tail-chopping routers is used for hedging of the latency across replicated
+//! routees. Both sends the request to the routees through ask, and respond to the original sender
+//! with the first received answer on behalf of the router. All later answers is ignored. If no one
+//! routee will answer in the specified time, sender receives the AskTimeoutError message.
+//!
+//! ```ignore
+//! // Request is sent to all 5 routees at once
+//! let props = Pool::scatter_gather_first_completed(5, Duration::from_secs(3))
+//!     .props(|| Props::new(tsafe!(Replica::new())));
+//!
+//! // Request is sent to one routee. If it does not answer in 100 ms, request is sent to the next
+//! // one, and so on.
+//! let props = Pool::tail_chopping(5, Duration::from_secs(3), Duration::from_millis(100))
+//!     .props(|| Props::new(tsafe!(Replica::new())));
+//! ```
+//!
+//! Own logics with a responses processing may be realized through overriding of the route method
+//! of the RoutingLogic trait.
+//!
+//! ## Resizer
+//!
+//! Pool router may change count of his routees in depend of the load. For this, resizer must be
+//! attached to the pool. Router periodically checks mailboxes of the routees and creates new
+//! routees if all of them is busy, or stops some routees if most of them is idle. Stopped routees
+//! processes all messages which already is in their mailboxes.
+//!
+//! ```ignore
+//! let resizer = Resizer::new(2, 16)
+//!     .set_pressure_threshold(10)
+//!     .set_interval(Duration::from_millis(500));
+//!
+//! let props = Pool::round_robin(2)
+//!     .with_resizer(resizer)
+//!     .props(|| Props::new(tsafe!(Worker::new())));
+//! ```
+//!
+//! Resizer does not works with group routers, because they does not creates own routees.
+//!
+//! ## Special messages
+//!
+//! If you need to send some message to all routees independently of the routing logic, you may wrap
+//! it to the Broadcast message:
+//!
+//! ```ignore
+//! router.tell(msg!(Broadcast::new(msg!(commands::Reload {}))), None);
+//! ```
+//!
+//! PoisonPill sent to the router is broadcasted to all routees. They will be stopped after they
+//! process all previous messages. Router watches his routees and removes terminated routees from
+//! the list. When the last routee is terminated, router stops itself.
+//!
 //! # Remoting
 //!
 //! Remoting is the feature which concludes in opportunity of interaction between two actor systems
@@ -972,16 +1446,72 @@
 //! How this technique may be used in practice you may see in 'examples/actor/remote'. First see to
 //! the mod.rs for to understand how this example is work.
 //!
+//! # Coordinated shutdown
+//!
+//! Call of the terminate method of the actor system does not stops actors immediately. Instead of
+//! this, the coordinated shutdown is run. It consists from the ordered list of named phases, to
+//! which you may register tasks. Next phases is exists (in the order of execution):
+//!
+//! * service-unbind - stop accepting of new remote connections. NetworkActorSystem stops his
+//! network controller in this phase.
+//! * service-requests-done - waiting of completion of requests processed by the service actors
+//! * service-stop - stopping of the service actors
+//! * flush - flushing of the buffers and other work which must be done before actors stopping
+//! * actor-system-terminate - last phase, after which all actors and dispatchers is stopped
+//!
+//! Each task is the function which blocks until his work will be completed. Tasks of the same phase
+//! is run in parallel, and the next phase is started only after all tasks of the previous phase
+//! was completed. Each phase has timeout (5 seconds by default). If some tasks does not completed
+//! within it, warning is logged and the next phase is started anyway.
+//!
+//! ```ignore
+//! let shutdown = system.coordinated_shutdown();
+//! let mut storage = storage.clone();
+//!
+//! shutdown.lock().unwrap().add_task(PHASE_FLUSH, "flush-storage", move || {
+//!     storage.ask(&mut (*system), msg!(storage::commands::Flush {})).wait();
+//! });
+//! shutdown.lock().unwrap().set_phase_timeout(PHASE_FLUSH, Duration::from_secs(30));
+//! ```
+//!
+//! Shutdown may be run by the terminate call, which blocks the caller until the actor system will
+//! be stopped. If the caller must not be blocked (for example it's an actor), use terminate_async
+//! or call CoordinatedShutdown::run directly. They return future which is completed after the
+//! actor system was stopped. In all cases phases is run in the separate thread, so tasks may
+//! freely use the actor system, even if the caller of terminate_async holds his lock. Shutdown is
+//! run only once, all repeated calls returns the same future.
+//!
+//! ```ignore
+//! system.terminate();
+//!
+//! // or without blocking
+//! system.terminate_async().ready(Duration::from_secs(30));
+//! ```
+//!
+//! On Linux, shutdown may be also run by SIGTERM or SIGINT signals. This is must be enabled
+//! explicitly. If the second argument is true, after shutdown process is exited with code 128 +
+//! number of the signal. Repeated signal kills the process immediately.
+//!
+//! ```ignore
+//! CoordinatedShutdown::enable_signals(&system.coordinated_shutdown(), true);
+//! ```
+//!
 #[macro_use] pub mod message;
 #[macro_use] pub mod error;
 pub mod prelude;
 pub mod dispatcher;
 pub mod default_dispatcher;
 pub mod pinned_dispatcher;
+pub mod work_stealing_dispatcher;
+pub mod balancing_dispatcher;
+pub mod calling_thread_dispatcher;
 pub mod actor_cell;
 pub mod envelope;
 pub mod mailbox;
 pub mod unbound_mailbox;
+pub mod bounded_mailbox;
+pub mod priority_mailbox;
+pub mod control_aware_mailbox;
 pub mod actor;
 pub mod local_actor_system;
 pub mod abstract_actor_ref;
@@ -989,6 +1519,7 @@ pub mod props;
 pub mod actor_path;
 pub mod actor_context;
 pub mod dead_letters;
+pub mod unhandled_message;
 pub mod synthetic_actor;
 pub mod actor_ref_factory;
 pub mod abstract_actor_system;
@@ -996,9 +1527,13 @@ pub mod local_actor_ref;
 pub mod scheduler;
 pub mod timers;
 pub mod watcher;
+pub mod event_stream;
+pub mod coordinated_shutdown;
 pub mod ask_actor;
 pub mod wrapped_dispatcher;
 pub mod stash;
 pub mod fsm;
 pub mod supervision;
+pub mod backoff_supervisor;
 pub mod remoting;
+pub mod routing;
\ No newline at end of file
@@ -3,14 +3,18 @@ use crate::executors::thread_pinned_executor::{ThreadPinnedExecutor, Distributio
 use crate::executors::executor::{Executor, ExecutorTask};
 use crate::actors::dispatcher::Dispatcher;
 use crate::actors::actor_cell::ActorCell;
+use crate::actors::watcher::StopReason;
 use crate::actors::local_actor_ref::LocalActorRef;
 use crate::actors::abstract_actor_ref::AbstractActorRef;
 use crate::actors::actor_context::ActorContext;
 use crate::actors::envelope::Envelope;
 use crate::actors::mailbox::Mailbox;
 use crate::actors::actor::{Actor, PoisonPill};
+use crate::actors::unhandled_message::UnhandledMessage;
 use crate::actors::message::Message;
+use crate::actors::error::Error;
 use crate::common::tsafe::TSafe;
+use std::panic::{self, AssertUnwindSafe};
 use std::any::Any;
 
 
@@ -31,7 +35,7 @@ impl PinnedDispatcher {
         }
     }
 
-    pub fn invoke(mailbox: &TSafe<Mailbox + Send>, actor: &TSafe<Actor + Send>, cell: &TSafe<ActorCell>) {
+    pub fn invoke(mailbox: &TSafe<Mailbox + Send>, _actor: &TSafe<Actor + Send>, cell: &TSafe<ActorCell>) {
         while true {
             let envelope = {
                 let mut mailbox = mailbox.lock().unwrap();
@@ -61,19 +65,38 @@ impl PinnedDispatcher {
 
                 let handled = {
                     let im = {
+                        // Actor is taken from the cell, because he may be replaced at restart
+                        let (behavior, actor) = {
+                            let cell = cell.lock().unwrap();
+                            (cell.current_behavior(), cell.actor.clone())
+                        };
                         let mut actor = actor.lock().unwrap();
                         let ctx = ActorContext::new(
                             sender.clone(),
                             envelope.receiver.clone(),
                             envelope.system.clone(),
                             cell.clone());
-                        actor.receive(msg.clone(), ctx)
+
+                        // Panic is caught while the actor and the behavior is still locked, so their mutexes will
+                        // not be poisoned. Panic is passed to the supervision as usual error.
+                        let mut behavior = behavior.as_ref().map(|b| b.lock().unwrap());
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                            match behavior.as_mut() {
+                                Some(behavior) => (&mut **behavior)(msg.clone(), ctx),
+                                None => actor.receive(msg.clone(), ctx)
+                            }
+                        }));
+
+                        match result {
+                            Ok(im) => im,
+                            Err(payload) => Err(Error::from_panic(payload))
+                        }
                     };
 
                     if im.is_ok() {
                         im.ok().unwrap()
                     } else {
-                        let f = cell.lock().unwrap().fail(im.err().unwrap(), cell.clone());
+                        let f = cell.lock().unwrap().fail(im.err().unwrap(), Some(msg.clone()), cell.clone());
                         f();
                         true
                     }
@@ -82,13 +105,7 @@ impl PinnedDispatcher {
                 if !handled {
                     let handled2 = PinnedDispatcher::internal_receive(mailbox, msg.clone(), cell);
                     if !handled2 {
-                        let mut dead_letters = {
-                            let mut system = envelope.system.lock().unwrap();
-                            let dead_letters = system.dead_letters();
-                            dead_letters
-                        };
-                        dead_letters.cell().lock().unwrap().send(&dead_letters.cell(), msg, Some(sender), envelope.receiver );
-
+                        UnhandledMessage::publish(&envelope.system, msg, sender, envelope.receiver);
                     }
                 }
             }
@@ -98,15 +115,18 @@ impl PinnedDispatcher {
     pub fn internal_receive(mailbox: &TSafe<Mailbox + Send>, msg: Message, cell: &TSafe<ActorCell>) -> bool {
 
         if let Some(PoisonPill {}) = msg.get().downcast_ref::<PoisonPill>() {
+            let reason;
             {
                 let mut cell_u = cell.lock().unwrap();
+                // Actor stopped through the stop call is suspended before the PoisonPill is sent to him
+                reason = if cell_u.suspended { StopReason::Normal } else { StopReason::PoisonPill };
                 cell_u.suspend();
                 let dead_letters = cell_u.system.lock().unwrap().dead_letters();
                 mailbox.lock().unwrap().clean_up(Box::new(LocalActorRef::new(cell.clone(), cell_u.path.clone())), dead_letters);
                 cell_u.dispatcher.lock().unwrap().stop();
             }
 
-            let f = cell.lock().unwrap().stop(cell.clone());
+            let f = cell.lock().unwrap().stop_with_reason(cell.clone(), Some(reason));
             f();
         } else {
             return false
@@ -152,4 +172,85 @@ impl Dispatcher for PinnedDispatcher {
 //    fn drop(&mut self) {
 //        println!("PinnedDispatcher dropped")
 //    }
-//}
\ No newline at end of file
+//}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::error::PanicError;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    /// Panics on the "panic" message, counts numbers and records failures
+    struct Panicker {
+        count: u32,
+        log: Arc<Mutex<Vec<String>>>
+    }
+
+    impl Actor for Panicker {
+        fn pre_fail(&mut self, _ctx: ActorContext, err: Error, strategy: SupervisionStrategy) {
+            let message = err.get().downcast_ref::<PanicError>().map(|e| e.message.clone());
+            self.log.lock().unwrap().push(format!("fail {:?} {:?}", message, strategy));
+        }
+
+        fn post_restart(&mut self, _ctx: ActorContext) {
+            self.log.lock().unwrap().push(String::from("restarted"));
+        }
+
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            let msg = msg.get();
+            if let Some(&"panic") = msg.downcast_ref::<&str>() {
+                panic!("boom");
+            }
+            match msg.downcast_ref::<u32>() {
+                Some(_) => {
+                    self.count = self.count + 1;
+                    self.log.lock().unwrap().push(format!("count {}", self.count));
+                },
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    /// Sends number, panic and number again to the actor on the specified dispatcher
+    fn panic_in_receive(dispatcher: &str, strategy: SupervisionStrategy, len: usize) -> Vec<String> {
+        let mut system = LocalActorSystem::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let props = Props::new(tsafe!(Panicker { count: 0, log: log.clone() }))
+            .with_dispatcher(dispatcher)
+            .with_supervision_strategy(strategy);
+        let mut actor = system.actor_of(props, None);
+
+        actor.tell(msg!(1 as u32), None);
+        actor.tell(msg!("panic"), None);
+        actor.tell(msg!(1 as u32), None);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < len && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(Duration::from_millis(50));
+
+        // Mutex of the actor is not poisoned by the panic
+        let cell = actor.cell();
+        assert!(!cell.lock().unwrap().actor.is_poisoned());
+
+        system.terminate();
+        let log = log.lock().unwrap().clone();
+        log
+    }
+
+    #[test]
+    fn panic_in_receive_is_resumed_by_supervision() {
+        let log = panic_in_receive("pinned", SupervisionStrategy::Resume, 3);
+        assert_eq!(log, vec!["count 1", "fail Some(\"boom\") Resume", "count 2"]);
+    }
+
+    #[test]
+    fn panic_in_receive_restarts_actor_by_supervision() {
+        let log = panic_in_receive("pinned", SupervisionStrategy::Restart, 4);
+        assert_eq!(log, vec!["count 1", "fail Some(\"boom\") Restart", "restarted", "count 2"]);
+    }
+}
@@ -1,6 +1,6 @@
 //! Prelude for work with actors
 
-pub use crate::actors::message::Message;
+pub use crate::actors::message::{Message, ControlMessage};
 pub use crate::actors::error::Error;
 pub use crate::actors::local_actor_system::LocalActorSystem;
 pub use crate::actors::actor_ref_factory::ActorRefFactory;
@@ -12,7 +12,11 @@ pub use crate::actors::timers::{Timers, RealTimers, StubTimers};
 pub use crate::actors::stash::{Stash, RealStash, StubStash};
 pub use crate::actors::abstract_actor_ref::ActorRef;
 pub use crate::actors::fsm::{Fsm, StateResult, FsmWrapper, StateTimeout};
-pub use crate::actors::supervision::SupervisionStrategy;
+pub use crate::actors::supervision::{SupervisionStrategy, SupervisorStrategy, RestartLimit};
+pub use crate::actors::event_stream::EventStream;
+pub use crate::actors::dead_letters::DeadLetter;
+pub use crate::actors::unhandled_message::UnhandledMessage;
+pub use crate::actors::coordinated_shutdown::CoordinatedShutdown;
 pub use crate::actors::remoting::messages_serializer::{MessagesSerializer, SerializationError, SerializedMessage};
 pub use crate::actors::remoting::delivery_error::{DeliveryErrorReason, DeliveryError};
 pub use crate::actors::remoting::remote_actor_system::RemoteActorSystem;
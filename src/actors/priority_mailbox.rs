@@ -0,0 +1,169 @@
+//! Predefined mailbox which orders messages by their priority
+//!
+//! Priority of each message is calculated by the user defined function. Messages with greater
+//! priority is dequeued first. Messages with equal priority is dequeued in the order in which they
+//! was enqueued. Queue of this mailbox does not have size restrictions.
+//!
+//! This mailbox can't be specified directly through Props, because it requires priority function.
+//! For use it, register it's factory in the actor system:
+//!
+//! ```ignore
+//! system.add_mailbox("prio", Box::new(|| tsafe!(PriorityMailbox::new(|msg| {
+//!     match_downcast_ref!(msg.get(), {
+//!         _m: Cancel => 10,
+//!         _ => 0
+//!     })
+//! }))));
+//! ```
+//!
+use crate::actors::mailbox::Mailbox;
+use crate::actors::envelope::Envelope;
+use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::message::Message;
+use std::collections::BinaryHeap;
+use std::cmp::Ordering;
+
+/// Envelope with computed priority and sequence number used for preserve the order of messages
+/// with equal priority
+struct PriorityEnvelope {
+    priority: i32,
+    seq: u64,
+    envelope: Envelope
+}
+
+impl PartialEq for PriorityEnvelope {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+
+impl Eq for PriorityEnvelope {}
+
+impl PartialOrd for PriorityEnvelope {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PriorityEnvelope {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is max-heap, so the earlier message must be 'greater' than the later
+        self.priority.cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+pub struct PriorityMailbox {
+    is_planned: bool,
+    queue: BinaryHeap<PriorityEnvelope>,
+    priority: Box<Fn(&Message) -> i32 + Send>,
+    seq: u64
+}
+
+impl PriorityMailbox {
+
+    /// Creates new mailbox with specified priority function. The greater value this function
+    /// returns, the earlier message will be processed.
+    pub fn new<F>(priority: F) -> PriorityMailbox
+        where F: 'static + Fn(&Message) -> i32 + Send
+    {
+        PriorityMailbox {
+            is_planned: false,
+            queue: BinaryHeap::new(),
+            priority: Box::new(priority),
+            seq: 0
+        }
+    }
+}
+
+impl Mailbox for PriorityMailbox {
+
+    fn set_planned(self: &mut Self, planned: bool) {
+        self.is_planned = planned;
+    }
+
+    fn is_planned(self: &Self) -> bool {
+        self.is_planned
+    }
+
+    fn enqueue(self: &mut Self, envelope: Envelope) {
+        let priority = (self.priority)(&envelope.message);
+        let seq = self.seq;
+        self.seq = self.seq + 1;
+
+        self.queue.push(PriorityEnvelope {
+            priority,
+            seq,
+            envelope
+        });
+    }
+
+    fn dequeue(self: &mut Self) -> Envelope {
+        self.queue.pop().unwrap().envelope
+    }
+
+    fn has_messages(self: &Self) -> bool {
+        self.queue.len() > 0
+    }
+
+    fn size(self: &Self) -> usize {
+        self.queue.len()
+    }
+
+    /// Drops all messages to the DeadLetter in the order of their priority
+    fn clean_up(self: &mut Self, sender: ActorRef, dead_letters: ActorRef) {
+        let mut dead_letters = dead_letters;
+
+        while let Some(entry) = self.queue.pop() {
+            dead_letters.tell(entry.envelope.message, Some(&sender));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::priority_mailbox::PriorityMailbox;
+    use crate::actors::mailbox::Mailbox;
+    use crate::actors::envelope::Envelope;
+    use crate::common::tsafe::TSafe;
+    use std::sync::{Arc, Mutex};
+
+    /// Priority of the number is his tens
+    fn mailbox(system: &mut LocalActorSystem, numbers: Vec<u32>) -> PriorityMailbox {
+        let mut mailbox = PriorityMailbox::new(|msg| (*msg.get().downcast_ref::<u32>().unwrap() / 10) as i32);
+        for n in numbers {
+            let boxed: TSafe<AbstractActorSystem + Send> = tsafe!(system.clone());
+            mailbox.enqueue(Envelope::new(msg!(n), None, system.dead_letters(), boxed));
+        }
+        mailbox
+    }
+
+    #[test]
+    fn messages_is_ordered_by_priority_and_by_arrival_within_priority() {
+        let mut system = LocalActorSystem::new();
+        let mut mailbox = mailbox(&mut system, vec![1, 21, 2, 22, 11, 3, 12]);
+        assert_eq!(mailbox.size(), 7);
+
+        let mut order = Vec::new();
+        while mailbox.has_messages() {
+            order.push(*mailbox.dequeue().message.get().downcast_ref::<u32>().unwrap());
+        }
+
+        assert_eq!(order, vec![21, 22, 11, 12, 1, 2, 3]);
+        system.terminate();
+    }
+
+    #[test]
+    fn clean_up_drops_all_messages_to_dead_letters() {
+        let mut system = LocalActorSystem::new();
+        let mut mailbox = mailbox(&mut system, vec![1, 21, 11]);
+
+        let dead_letters = system.dead_letters();
+        mailbox.clean_up(system.dead_letters(), dead_letters);
+
+        assert!(!mailbox.has_messages());
+        assert_eq!(system.dead_letters_count(), 3);
+        system.terminate();
+    }
+}
@@ -3,30 +3,62 @@
 //! This structure is used, for indicates to ActorSystem, how to create an actor
 
 use crate::actors::actor::Actor;
-use crate::actors::supervision::SupervisionStrategy;
+use crate::actors::supervision::{SupervisionStrategy, SupervisorStrategy, RestartLimit};
+use crate::actors::mailbox::MailboxType;
+use crate::actors::bounded_mailbox::OverflowStrategy;
 use crate::common::tsafe::TSafe;
+use std::sync::{Arc, Mutex};
+
+/// Constructor of the actor instance. Used for creating of fresh instance at actor restart.
+pub type ActorFactory = Box<Fn() -> TSafe<Actor + Send> + Send>;
 
 pub struct Props {
 
     /// User defined actor instance
     pub actor: TSafe<Actor + Send>,
 
+    /// Constructor of the actor instance. If exists, actor is recreated at each restart.
+    pub factory: Option<ActorFactory>,
+
     /// Name of dispatcher on which actor must work
     pub dispatcher: String,
 
     /// Supervision strategy for the actor instance
-    pub supervision_strategy: SupervisionStrategy
+    pub supervision_strategy: SupervisionStrategy,
+
+    /// Strategy with which the actor supervises his childs
+    pub supervisor_strategy: Option<TSafe<SupervisorStrategy>>,
+
+    /// Limit of restarts of the actor instance
+    pub restart_limit: Option<RestartLimit>,
+
+    /// Type of mailbox which will be created for the actor
+    pub mailbox: MailboxType
 }
 
 impl Props {
     pub fn new(actor: TSafe<Actor + Send>) -> Props {
         Props {
             actor,
+            factory: None,
             dispatcher: String::from("default"),
-            supervision_strategy: SupervisionStrategy::Restart
+            supervision_strategy: SupervisionStrategy::Restart,
+            supervisor_strategy: None,
+            restart_limit: None,
+            mailbox: MailboxType::Unbound
         }
     }
 
+    /// Creates props with the actor constructor. First actor instance is created immediately.
+    /// At each restart the actor will be replaced by the new instance, so his state will be reset.
+    /// Actor created through Props::new keeps his state between restarts.
+    pub fn from_factory<F>(factory: F) -> Props
+        where F: Fn() -> TSafe<Actor + Send> + Send + 'static {
+        let mut props = Props::new(factory());
+        props.factory = Some(Box::new(factory));
+        props
+    }
+
     /// Sets dispatcher name on which the actor must work. By default exists two type of
     /// dispatchers: 'default' and 'pinned'. If you want to use other dispatchers types, you need
     /// register it's in the actor system.
@@ -40,4 +72,39 @@ impl Props {
         self.supervision_strategy = strategy;
         self
     }
+
+    /// Sets strategy with which the actor will supervise his childs. This strategy overrides
+    /// supervision strategies of the childs props.
+    pub fn with_supervisor_strategy(mut self, strategy: SupervisorStrategy) -> Props {
+        self.supervisor_strategy = Some(tsafe!(strategy));
+        self
+    }
+
+    /// Sets limit of restarts for the actor instance. Without the limit, permanently failing actor
+    /// will be restarted forever.
+    pub fn with_restart_limit(mut self, limit: RestartLimit) -> Props {
+        self.restart_limit = Some(limit);
+        self
+    }
+
+    /// Sets bounded mailbox with specified capacity and overflow strategy for the actor instance.
+    /// By default actor uses unbounded mailbox.
+    pub fn with_bounded_mailbox(mut self, capacity: usize, strategy: OverflowStrategy) -> Props {
+        self.mailbox = MailboxType::Bounded(capacity, strategy);
+        self
+    }
+
+    /// Sets control aware mailbox for the actor instance. In this mailbox control messages, such
+    /// as PoisonPill, is processed before all other messages.
+    pub fn with_control_aware_mailbox(mut self) -> Props {
+        self.mailbox = MailboxType::ControlAware;
+        self
+    }
+
+    /// Sets name of the mailbox which will be created for the actor instance. Mailbox with this
+    /// name must be registered in the actor system through add_mailbox call.
+    pub fn with_mailbox(mut self, name: &str) -> Props {
+        self.mailbox = MailboxType::Named(String::from(name));
+        self
+    }
 }
\ No newline at end of file
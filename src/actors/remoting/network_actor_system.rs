@@ -7,9 +7,11 @@ use crate::actors::actor_ref_factory::{ActorRefFactory, ActorSelectError};
 use crate::actors::abstract_actor_system::AbstractActorSystem;
 use crate::actors::props::Props;
 use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::error::Error;
 use crate::actors::scheduler::Scheduler;
 use crate::actors::dispatcher::Dispatcher;
-use crate::actors::watcher::WatchingEvents;
+use crate::actors::mailbox::MailboxFactory;
+use crate::actors::watcher::{WatchingEvents, StopReason};
 use crate::executors::executor::Executor;
 use crate::actors::remoting::messages_serializer::MessagesSerializer;
 use crate::actors::remoting::net_controller::NetController;
@@ -28,18 +30,20 @@ use crate::actors::actor_cell::ActorCell;
 use crate::actors::actor::PoisonPill;
 use crate::actors::default_dispatcher::DefaultDispatcher;
 use crate::actors::pinned_dispatcher::PinnedDispatcher;
-use crate::actors::dead_letters::DeadLetters;
+use crate::actors::dead_letters::{DeadLetters, DeadLetter};
 use crate::actors::synthetic_actor::SyntheticActor;
 use crate::actors::unbound_mailbox::UnboundMailbox;
 use crate::actors::local_actor_ref::LocalActorRef;
 use crate::actors::watcher::Watcher;
+use crate::actors::event_stream::EventStream;
+use crate::actors::coordinated_shutdown::{CoordinatedShutdown, PHASE_SERVICE_UNBIND};
 use crate::actors::wrapped_dispatcher::WrappedDispatcher;
 use crate::actors::supervision::SupervisionStrategy;
 use crate::futures::future::Future;
-use std::mem;
 use std::collections::hash_map::HashMap;
 use std::collections::vec_deque::VecDeque;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::net::{TcpStream, SocketAddr};
 use std::thread;
 use std::time::Duration;
@@ -54,6 +58,9 @@ pub struct NetworkActorSystem {
     /// Default dispatcher. Used if other dispatcher does not explicitly specified.
     dispatchers: TSafe<HashMap<String, TSafe<Dispatcher + Send>>>,
 
+    /// Registered mailboxes factories. Used for creating of mailboxes specified by name in Props.
+    mailboxes: TSafe<HashMap<String, MailboxFactory>>,
+
     /// Dead letter actor reference. Sending message through this reference has is very low cost,
     /// because message after drop to the mailbox, is simply destroyed without hes subsequent
     /// execution planning. */
@@ -65,6 +72,22 @@ pub struct NetworkActorSystem {
     /// Watcher event bus
     watcher: TSafe<Watcher>,
 
+    /// Publish/subscribe bus of the system
+    event_stream: EventStream,
+
+    /// Total count of messages dropped to the dead letters
+    dead_letters_count: Arc<AtomicUsize>,
+
+    /// Channel to the thread which publishes DeadLetter events. Shared by all dead letters
+    /// mailboxes of the system.
+    dead_letters_publisher: Sender<DeadLetter>,
+
+    /// Is unhandled messages must be logged
+    log_unhandled: Arc<AtomicBool>,
+
+    /// Ordered phases of the system shutdown
+    coordinated_shutdown: TSafe<CoordinatedShutdown>,
+
     /// Root guardian synthetic actor
     root: Option<TSafe<ActorCell>>,
 
@@ -86,15 +109,23 @@ impl NetworkActorSystem {
         dispatchers.insert(String::from("default"), def_dispatch.clone());
 
         let root_path = tsafe!(ActorPath::new("root", None));
+        let event_stream = EventStream::new();
+        let dead_letters_publisher = DeadLetters::spawn_publisher(event_stream.clone());
 
         let mut system = NetworkActorSystem {
             nids: tsafe!(0),
             dispatchers: tsafe!(dispatchers),
+            mailboxes: tsafe!(HashMap::new()),
             dead_letters: None,
             root: None,
             root_path: root_path.clone(),
             scheduler: tsafe!(Scheduler::new()),
-            watcher: tsafe!(Watcher::new()),
+            watcher: tsafe!(Watcher::new(event_stream.clone())),
+            event_stream,
+            dead_letters_count: Arc::new(AtomicUsize::new(0)),
+            dead_letters_publisher,
+            log_unhandled: Arc::new(AtomicBool::new(false)),
+            coordinated_shutdown: tsafe!(CoordinatedShutdown::new()),
             controller: None,
             boxed_self: None
         };
@@ -115,7 +146,7 @@ impl NetworkActorSystem {
         let root_safe = tsafe!(root);
 
         let dlp = tsafe!(ActorPath::new("deadLetters", Some(root_path.clone())));
-        let dlm = DeadLetters::new();
+        let dlm = DeadLetters::new(dlp.lock().unwrap().clone(), system.dead_letters_publisher.clone(), system.dead_letters_count.clone());
         let mut dlc = ActorCell::new(
             system_safe.clone(),
             dlp.clone(),
@@ -144,8 +175,33 @@ impl NetworkActorSystem {
         system.controller = Some(net_controller);
         boxed_dlc.lock().unwrap().start(boxed_dlc.clone());
 
+        // Remote connections is not accepted more from the first phase of the shutdown
+        let controller = system.controller.as_ref().unwrap().clone();
+        system.coordinated_shutdown.lock().unwrap().add_task(PHASE_SERVICE_UNBIND, "stop-remoting", move || {
+            controller.lock().unwrap().stop();
+        });
+
+        // Weak handles, because the coordinated shutdown is owned by the system itself
+        let root = Arc::downgrade(system.root.as_ref().unwrap());
+        let dispatchers = Arc::downgrade(&system.dispatchers);
+        system.coordinated_shutdown.lock().unwrap().set_terminator(move || {
+            if let (Some(root), Some(dispatchers)) = (root.upgrade(), dispatchers.upgrade()) {
+                NetworkActorSystem::stop_system(&root, &dispatchers);
+            }
+        });
+
         system
     }
+
+    /// Stops all actors and dispatchers. Called by the coordinated shutdown after the last phase.
+    fn stop_system(root: &TSafe<ActorCell>, dispatchers: &TSafe<HashMap<String, TSafe<Dispatcher + Send>>>) {
+        let f = root.lock().unwrap().stop_with_reason(root.clone(), Some(StopReason::SystemShutdown));
+        f();
+        let d_list = dispatchers.lock().unwrap();
+        for (_, d) in d_list.iter() {
+            d.lock().unwrap().stop();
+        }
+    }
 }
 
 impl ActorRefFactory for NetworkActorSystem {
@@ -160,7 +216,7 @@ impl ActorRefFactory for NetworkActorSystem {
       /// ```
       ///
     fn actor_of(self: &mut Self, props: Props, name: Option<&str>) -> ActorRef {
-        let mailbox = tsafe!(UnboundMailbox::new());
+        let mailbox = props.mailbox.create(&self.mailboxes.lock().unwrap());
 
         let mut aname: String;
 
@@ -194,7 +250,7 @@ impl ActorRefFactory for NetworkActorSystem {
         };
 
 
-        let cell = ActorCell::new(
+        let mut cell = ActorCell::new(
             tsafe!(self.clone()),
             path.clone(),
             props.actor,
@@ -204,6 +260,9 @@ impl ActorRefFactory for NetworkActorSystem {
             self.root.clone(),
             props.supervision_strategy
         );
+        cell.supervisor_strategy = props.supervisor_strategy;
+        cell.restart_limit = props.restart_limit;
+        cell.factory = props.factory;
         let boxed_cell = tsafe!(cell);
 
         {
@@ -308,6 +367,13 @@ impl ActorRefFactory for NetworkActorSystem {
         self.watcher.lock().unwrap().watch(watcher, observed);
     }
 
+    /// Register watcher for receive all 'watching events' from observed actor, including
+    /// Restarted and Failed events
+    fn watch_lifecycle(&mut self, watcher: &ActorRef, observed: &ActorRef) -> Result<(), Error> {
+        self.watcher.lock().unwrap().watch_lifecycle(watcher, observed);
+        Ok(())
+    }
+
     /// Unregister watcher from receive 'watching events' from observed actor
     fn unwatch(&mut self, watcher: &ActorRef, observed: &ActorRef) {
         self.watcher.lock().unwrap().unwatch(watcher, observed);
@@ -320,23 +386,40 @@ impl AbstractActorSystem for NetworkActorSystem {
         self.scheduler.clone()
     }
 
+    /// Returns actor system event stream
+    fn event_stream(&self) -> EventStream {
+        self.event_stream.clone()
+    }
+
+    /// Returns total count of messages dropped to the dead letters
+    fn dead_letters_count(&self) -> usize {
+        self.dead_letters_count.load(Ordering::Relaxed)
+    }
+
+    /// Enables or disables logging of unhandled messages with warning level
+    fn set_log_unhandled(&mut self, enabled: bool) {
+        self.log_unhandled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Is unhandled messages is logged
+    fn is_log_unhandled(&self) -> bool {
+        self.log_unhandled.load(Ordering::Relaxed)
+    }
+
     /// Register new watching event from the specified actor
     fn register_watch_event(&self, from: &ActorRef, event: WatchingEvents) {
         self.watcher.lock().unwrap().register_event(&from, event);
     }
 
-    /// Stops the actor system
-    fn terminate(&mut self) {
-        let mut root = self.root.as_ref().unwrap().clone();
-        root.lock().unwrap().stop(root.clone());
-        let d_list = self.dispatchers.lock().unwrap();
-        for (_, d) in d_list.iter() {
-            d.lock().unwrap().stop();
-        }
-        self.dead_letters = None;
+    /// Stops the actor system through the coordinated shutdown without blocking of the caller
+    fn terminate_async(&mut self) -> WrappedFuture<(), ()> {
+        self.controller = None;
+        CoordinatedShutdown::run(&self.coordinated_shutdown)
+    }
 
-        let controller = mem::replace(&mut self.controller, None);
-        controller.unwrap().lock().unwrap().stop();
+    /// Returns coordinated shutdown of the system
+    fn coordinated_shutdown(&self) -> TSafe<CoordinatedShutdown> {
+        self.coordinated_shutdown.clone()
     }
 
     /// Adds new dispatcher to the system. For now supporter only default dispatcher replacing
@@ -357,7 +440,7 @@ impl AbstractActorSystem for NetworkActorSystem {
                 let root_safe = tsafe!(root);
 
                 let dlp = tsafe!(ActorPath::new("deadLetters", Some(self.root_path.clone())));
-                let dlm = DeadLetters::new();
+                let dlm = DeadLetters::new(dlp.lock().unwrap().clone(), self.dead_letters_publisher.clone(), self.dead_letters_count.clone());
                 let mut dlc = ActorCell::new(
                     tsafe!(self.clone()),
                     dlp.clone(),
@@ -403,6 +486,23 @@ impl AbstractActorSystem for NetworkActorSystem {
         self.dispatchers.clone()
     }
 
+    /// Adds new mailbox factory to the system. Mailbox created by it may be specified for an actor
+    /// through Props::with_mailbox.
+    fn add_mailbox(&mut self, name: &str, factory: MailboxFactory) {
+        let name_str = String::from(name);
+        let mut mailboxes = self.mailboxes.lock().unwrap();
+
+        if !mailboxes.contains_key(&name_str) {
+            mailboxes.insert(name_str, factory);
+        } else {
+            panic!("Try to add mailbox with existed name '{}'", name_str)
+        }
+    }
+
+    fn get_mailboxes(&self) -> TSafe<HashMap<String, MailboxFactory>> {
+        self.mailboxes.clone()
+    }
+
     /// Returns dispatcher by name as executor
     fn get_executor(&self, name: &str) -> TSafe<Executor + Send> {
         tsafe!(WrappedDispatcher::new(self.get_dispatcher(name)))
@@ -437,11 +537,17 @@ impl Clone for NetworkActorSystem {
         NetworkActorSystem {
             nids: self.nids.clone(),
             dispatchers: self.dispatchers.clone(),
+            mailboxes: self.mailboxes.clone(),
             dead_letters: dead_letter, //self.dead_letters.clone()
             root,
             root_path: self.root_path.clone(),
             scheduler: self.scheduler.clone(),
             watcher: self.watcher.clone(),
+            event_stream: self.event_stream.clone(),
+            dead_letters_count: self.dead_letters_count.clone(),
+            dead_letters_publisher: self.dead_letters_publisher.clone(),
+            log_unhandled: self.log_unhandled.clone(),
+            coordinated_shutdown: self.coordinated_shutdown.clone(),
             controller: self.controller.clone(),
             boxed_self: self.boxed_self.clone()
         }
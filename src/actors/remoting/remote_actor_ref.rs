@@ -90,6 +90,10 @@ impl AbstractActorRef for RemoteActorRef {
     fn as_any(self: &Self) -> Box<Any> {
         Box::new(self.inner_clone())
     }
+
+    fn is_local(self: &Self) -> bool {
+        false
+    }
 }
 
 impl fmt::Display for RemoteActorRef {
@@ -15,7 +15,11 @@ use crate::actors::actor::Actor;
 use crate::actors::remoting::net_controller::NetController;
 use crate::actors::scheduler::Scheduler;
 use crate::actors::dispatcher::Dispatcher;
-use crate::actors::watcher::WatchingEvents;
+use crate::actors::mailbox::MailboxFactory;
+use crate::actors::watcher::{WatchingEvents, LifecycleWatchError};
+use crate::actors::error::Error;
+use crate::actors::event_stream::EventStream;
+use crate::actors::coordinated_shutdown::CoordinatedShutdown;
 use crate::actors::remoting::connection::{ConnectionData, ClientConnection};
 use crate::actors::remoting::packet::{Packet, Opm};
 use crate::actors::actor_path::ActorPath;
@@ -25,6 +29,7 @@ use crate::futures::future::WrappedFuture;
 use crate::actors::remoting::messages_serializer::MessagesSerializer;
 use bytes::{BytesMut, Bytes, Buf, BufMut, BigEndian};
 use std::sync::{Mutex, Arc};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::collections::HashMap;
 use std::net::{TcpStream, SocketAddr};
 use std::thread;
@@ -35,13 +40,40 @@ use std::sync::mpsc::{Receiver, Sender};
 
 
 pub struct RemoteActorSystem {
-    controller: RemoteNetController
+    controller: RemoteNetController,
+
+    /// Registered mailboxes factories. Remote system does not create actors itself, so this is
+    /// only a local registry, which is not transferred to the remote side.
+    mailboxes: TSafe<HashMap<String, MailboxFactory>>,
+
+    /// Publish/subscribe bus of the system. Events of the remote side is not transferred through
+    /// the network, so only messages published locally is delivered to the subscribers.
+    event_stream: EventStream,
+
+    /// Is unhandled messages must be logged
+    log_unhandled: Arc<AtomicBool>,
+
+    /// Ordered phases of the system shutdown. Remote system does not have own actors, so after
+    /// the phases only the connection is closed.
+    coordinated_shutdown: TSafe<CoordinatedShutdown>
 }
 
 impl RemoteActorSystem {
     pub fn new(addr: SocketAddr, host_system: TSafe<ActorRefFactory + Send>, messages_serializer: TSafe<MessagesSerializer + Send>) -> RemoteActorSystem {
+        let controller = RemoteNetController::new(addr, messages_serializer, host_system);
+
+        let mut coordinated_shutdown = CoordinatedShutdown::new();
+        let mut terminator_controller = controller.clone();
+        coordinated_shutdown.set_terminator(move || {
+            terminator_controller.stop();
+        });
+
         RemoteActorSystem {
-            controller: RemoteNetController::new(addr, messages_serializer, host_system)
+            controller,
+            mailboxes: tsafe!(HashMap::new()),
+            event_stream: EventStream::new(),
+            log_unhandled: Arc::new(AtomicBool::new(false)),
+            coordinated_shutdown: tsafe!(coordinated_shutdown)
         }
     }
 }
@@ -68,6 +100,14 @@ impl ActorRefFactory for RemoteActorSystem {
         unimplemented!()
     }
 
+    /// Failed and Restarted events is not transferred through the network, so lifecycle of the
+    /// remote actor can not be watched
+    fn watch_lifecycle(&mut self, _watcher: &ActorRef, observed: &ActorRef) -> Result<(), Error> {
+        Err(err!(LifecycleWatchError {
+            description: format!("Lifecycle of the remote actor {} can not be watched", observed)
+        }))
+    }
+
     fn unwatch(&mut self, watcher: &ActorRef, observed: &ActorRef) {
         unimplemented!()
     }
@@ -79,12 +119,39 @@ impl AbstractActorSystem for RemoteActorSystem {
         unimplemented!()
     }
 
+    /// Returns local event stream of the system
+    fn event_stream(&self) -> EventStream {
+        self.event_stream.clone()
+    }
+
+    /// Remote system does not have own dead letters, messages which is not delivered on the remote
+    /// side is counted by the remote system itself. So here it's always zero.
+    fn dead_letters_count(&self) -> usize {
+        0
+    }
+
+    /// Enables or disables logging of unhandled messages with warning level
+    fn set_log_unhandled(&mut self, enabled: bool) {
+        self.log_unhandled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Is unhandled messages is logged
+    fn is_log_unhandled(&self) -> bool {
+        self.log_unhandled.load(Ordering::Relaxed)
+    }
+
     fn register_watch_event(&self, from: &ActorRef, event: WatchingEvents) {
         unimplemented!()
     }
 
-    fn terminate(&mut self) {
-        self.controller.stop()
+    /// Closes the connection through the coordinated shutdown without blocking of the caller
+    fn terminate_async(&mut self) -> WrappedFuture<(), ()> {
+        CoordinatedShutdown::run(&self.coordinated_shutdown)
+    }
+
+    /// Returns coordinated shutdown of the system
+    fn coordinated_shutdown(&self) -> TSafe<CoordinatedShutdown> {
+        self.coordinated_shutdown.clone()
     }
 
     fn add_dispatcher(&mut self, name: &str, dispatcher: TSafe<Dispatcher + Send>) {
@@ -99,6 +166,23 @@ impl AbstractActorSystem for RemoteActorSystem {
         unimplemented!()
     }
 
+    /// Adds new mailbox factory to the local registry of the system
+    fn add_mailbox(&mut self, name: &str, factory: MailboxFactory) {
+        let name_str = String::from(name);
+        let mut mailboxes = self.mailboxes.lock().unwrap();
+
+        if !mailboxes.contains_key(&name_str) {
+            mailboxes.insert(name_str, factory);
+        } else {
+            panic!("Try to add mailbox with existed name '{}'", name_str)
+        }
+    }
+
+    /// Returns mailboxes factories from the local registry
+    fn get_mailboxes(&self) -> TSafe<HashMap<String, MailboxFactory>> {
+        self.mailboxes.clone()
+    }
+
     fn get_executor(&self, name: &str) -> TSafe<Executor + Send> {
         unimplemented!()
     }
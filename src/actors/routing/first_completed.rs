@@ -0,0 +1,355 @@
+//! Routing logics which sends a request to the few routees and responds with the first answer
+//!
+//! Both logics sends requests to the routees through ask. First successful response is sent to the
+//! original sender on behalf of the router. All later responses is ignored. If no one routee was
+//! respond in the specified time, original sender will receive AskTimeoutError message.
+
+use crate::actors::abstract_actor_ref::{ActorRef, AskTimeoutError};
+use crate::actors::abstract_actor_system::AbstractActorSystem;
+use crate::actors::actor_context::ActorContext;
+use crate::actors::message::Message;
+use crate::actors::scheduler::TaskGuard;
+use crate::actors::routing::routing_logic::RoutingLogic;
+use crate::common::tsafe::TSafe;
+use rand::seq::SliceRandom;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// State of the single routed request
+struct Completion {
+
+    /// Response was already sent to the original sender
+    done: bool,
+
+    /// Count of requests which was not completed yet
+    pending: usize,
+
+    /// Original sender of the request
+    sender: ActorRef,
+
+    /// Router
+    self_: ActorRef,
+
+    /// Planned requests of the tail-chopping logic
+    guards: Vec<TaskGuard>
+}
+
+impl Completion {
+    fn new(pending: usize, ctx: &ActorContext) -> TSafe<Completion> {
+        tsafe!(Completion {
+            done: false,
+            pending,
+            sender: ctx.sender.clone(),
+            self_: ctx.self_.clone(),
+            guards: Vec::new()
+        })
+    }
+
+    /// Handles result of the one of requests
+    fn on_result(&mut self, result: &Result<Message, AskTimeoutError>) {
+        if self.done {
+            return;
+        }
+
+        match result {
+            Ok(msg) => {
+                self.done = true;
+                self.guards.clear();
+                self.sender.tell(msg.clone(), Some(&self.self_));
+            },
+            Err(_) => {
+                self.pending = self.pending - 1;
+                if self.pending == 0 {
+                    self.done = true;
+                    self.sender.tell(msg!(AskTimeoutError {}), Some(&self.self_));
+                }
+            }
+        }
+    }
+}
+
+/// Sends request to the routee through ask and passes it result to the completion
+fn ask(routee: &ActorRef, system: &mut AbstractActorSystem, timeout: Duration, msg: Message, completion: &TSafe<Completion>) {
+    let completion = completion.clone();
+    (*routee).clone()
+        .ask_timeout(system, timeout, msg)
+        .on_complete(move |result| {
+            completion.lock().unwrap().on_result(result);
+        });
+}
+
+/// Sends the request to all routees at the same time
+pub struct ScatterGatherFirstCompletedRoutingLogic {
+
+    /// Maximum time of waiting of the first response
+    within: Duration
+}
+
+impl ScatterGatherFirstCompletedRoutingLogic {
+    pub fn new(within: Duration) -> ScatterGatherFirstCompletedRoutingLogic {
+        ScatterGatherFirstCompletedRoutingLogic {
+            within
+        }
+    }
+}
+
+impl RoutingLogic for ScatterGatherFirstCompletedRoutingLogic {
+    fn select(&mut self, _msg: &Message, routees: &Vec<ActorRef>) -> Vec<ActorRef> {
+        routees.iter().map(|r| (*r).clone()).collect()
+    }
+
+    fn route(&mut self, msg: Message, ctx: &mut ActorContext, routees: &Vec<ActorRef>) -> bool {
+        let targets = self.select(&msg, routees);
+        if targets.len() == 0 {
+            return false;
+        }
+
+        let completion = Completion::new(targets.len(), ctx);
+        for target in targets.iter() {
+            ask(target, &mut *ctx.system(), self.within, msg.clone(), &completion);
+        }
+
+        true
+    }
+}
+
+/// Sends the request to the random routee. If it does not respond in the specified interval, sends
+/// the request to the next random routee, and so on, until someone will respond or the whole time
+/// will be expired.
+pub struct TailChoppingRoutingLogic {
+
+    /// Maximum time of waiting of the first response
+    within: Duration,
+
+    /// Interval between requests to the next routees
+    interval: Duration
+}
+
+impl TailChoppingRoutingLogic {
+    pub fn new(within: Duration, interval: Duration) -> TailChoppingRoutingLogic {
+        TailChoppingRoutingLogic {
+            within,
+            interval
+        }
+    }
+}
+
+impl RoutingLogic for TailChoppingRoutingLogic {
+    fn select(&mut self, _msg: &Message, routees: &Vec<ActorRef>) -> Vec<ActorRef> {
+        let mut targets: Vec<ActorRef> = routees.iter().map(|r| (*r).clone()).collect();
+        targets.shuffle(&mut rand::thread_rng());
+        targets
+    }
+
+    fn route(&mut self, msg: Message, ctx: &mut ActorContext, routees: &Vec<ActorRef>) -> bool {
+        let mut targets = self.select(&msg, routees);
+
+        // Requests which can't be sent before the whole timeout expiration is not needed
+        let mut count = 0;
+        while count < targets.len() && self.interval * (count as u32) < self.within {
+            count = count + 1;
+        }
+        targets.truncate(count);
+
+        if targets.len() == 0 {
+            return false;
+        }
+
+        let completion = Completion::new(targets.len(), ctx);
+        let scheduler = ctx.system().get_scheduler();
+
+        for (i, target) in targets.into_iter().enumerate() {
+            let delay = self.interval * i as u32;
+            let timeout = self.within - delay;
+
+            if i == 0 {
+                ask(&target, &mut *ctx.system(), timeout, msg.clone(), &completion);
+            } else {
+                let system = ctx.system.clone();
+                let msg = msg.clone();
+                let completion_cloned = completion.clone();
+
+                let guard = scheduler.lock().unwrap().schedule_once(delay, move || {
+                    if completion_cloned.lock().unwrap().done {
+                        return;
+                    }
+
+                    ask(&target, &mut *system.lock().unwrap(), timeout, msg.clone(), &completion_cloned);
+                });
+
+                completion.lock().unwrap().guards.push(guard);
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::abstract_actor_ref::AskTimeoutError;
+    use crate::actors::routing::group::Group;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    /// Responds with his id after the delay, or never if the delay is not set
+    struct Responder {
+        id: u32,
+        delay: Option<Duration>,
+        asked: Arc<Mutex<Vec<u32>>>
+    }
+
+    impl Actor for Responder {
+        fn receive(&mut self, msg: Message, ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<String>() {
+                Some(_) => {
+                    self.asked.lock().unwrap().push(self.id);
+                    if let Some(delay) = self.delay {
+                        let id = self.id;
+                        let mut sender = ctx.sender.clone();
+                        let self_ = ctx.self_.clone();
+                        thread::spawn(move || {
+                            thread::sleep(delay);
+                            sender.tell(msg!(id), Some(&self_));
+                        });
+                    }
+                },
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    /// Records responses of the router. Timeout is recorded as None.
+    struct Client {
+        responses: Arc<Mutex<Vec<(Option<u32>, String)>>>
+    }
+
+    impl Actor for Client {
+        fn receive(&mut self, msg: Message, ctx: ActorContext) -> HandleResult {
+            let response = {
+                let msg = msg.get();
+                if let Some(id) = msg.downcast_ref::<u32>() {
+                    Some(*id)
+                } else if msg.is::<AskTimeoutError>() {
+                    None
+                } else {
+                    return Ok(false)
+                }
+            };
+            self.responses.lock().unwrap().push((response, ctx.sender.path().to_string()));
+            Ok(true)
+        }
+    }
+
+    struct Setup {
+        system: LocalActorSystem,
+        asked: Arc<Mutex<Vec<u32>>>,
+        responses: Arc<Mutex<Vec<(Option<u32>, String)>>>,
+        client: ActorRef
+    }
+
+    fn setup(delays: Vec<Option<u64>>) -> (Setup, Vec<String>) {
+        let mut system = LocalActorSystem::new();
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let responses = Arc::new(Mutex::new(Vec::new()));
+
+        let mut paths = Vec::new();
+        for (id, delay) in delays.into_iter().enumerate() {
+            let actor = Responder { id: id as u32, delay: delay.map(Duration::from_millis), asked: asked.clone() };
+            let name = format!("r{}", id);
+            system.actor_of(Props::new(tsafe!(actor)), Some(&name));
+            paths.push(format!("/root/{}", name));
+        }
+        let client = system.actor_of(Props::new(tsafe!(Client { responses: responses.clone() })), None);
+
+        (Setup { system, asked, responses, client }, paths)
+    }
+
+    fn wait_len<T>(log: &Arc<Mutex<Vec<T>>>, len: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < len && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn scatter_gather_responds_with_first_reply_and_drops_later() {
+        let (mut s, paths) = setup(vec![Some(300), Some(10), Some(150)]);
+        let paths: Vec<&str> = paths.iter().map(|p| &p[..]).collect();
+        let props = Group::scatter_gather_first_completed(paths, Duration::from_secs(2)).props();
+        let mut router = s.system.actor_of(props, Some("router"));
+
+        router.tell(msg!(String::from("request")), Some(&s.client));
+
+        wait_len(&s.asked, 3);
+        wait_len(&s.responses, 1);
+        thread::sleep(Duration::from_millis(500));
+
+        assert_eq!(*s.responses.lock().unwrap(), vec![(Some(1), String::from("/root/router"))]);
+
+        s.system.terminate();
+    }
+
+    #[test]
+    fn scatter_gather_sends_timeout_error_to_original_sender() {
+        let (mut s, paths) = setup(vec![None, None]);
+        let paths: Vec<&str> = paths.iter().map(|p| &p[..]).collect();
+        let props = Group::scatter_gather_first_completed(paths, Duration::from_millis(100)).props();
+        let mut router = s.system.actor_of(props, Some("router"));
+
+        router.tell(msg!(String::from("request")), Some(&s.client));
+
+        wait_len(&s.responses, 1);
+        thread::sleep(Duration::from_millis(200));
+
+        assert_eq!(s.asked.lock().unwrap().len(), 2);
+        assert_eq!(*s.responses.lock().unwrap(), vec![(None, String::from("/root/router"))]);
+
+        s.system.terminate();
+    }
+
+    #[test]
+    fn tail_chopping_stops_scheduling_after_reply() {
+        let (mut s, paths) = setup(vec![Some(0), Some(0), Some(0), Some(0)]);
+        let paths: Vec<&str> = paths.iter().map(|p| &p[..]).collect();
+        let props = Group::tail_chopping(paths, Duration::from_secs(2), Duration::from_millis(100)).props();
+        let mut router = s.system.actor_of(props, Some("router"));
+
+        router.tell(msg!(String::from("request")), Some(&s.client));
+
+        wait_len(&s.responses, 1);
+        thread::sleep(Duration::from_millis(500));
+
+        assert_eq!(s.asked.lock().unwrap().len(), 1);
+        let responses = s.responses.lock().unwrap().clone();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].0, Some(s.asked.lock().unwrap()[0]));
+
+        s.system.terminate();
+    }
+
+    #[test]
+    fn tail_chopping_asks_next_routees_while_no_reply_and_then_times_out() {
+        let (mut s, paths) = setup(vec![None, None, None, None, None]);
+        let paths: Vec<&str> = paths.iter().map(|p| &p[..]).collect();
+        let props = Group::tail_chopping(paths, Duration::from_millis(250), Duration::from_millis(100)).props();
+        let mut router = s.system.actor_of(props, Some("router"));
+
+        router.tell(msg!(String::from("request")), Some(&s.client));
+
+        wait_len(&s.responses, 1);
+        thread::sleep(Duration::from_millis(200));
+
+        // Requests is sent at 0, 100 and 200 ms, others would not fit to the whole timeout
+        let mut asked = s.asked.lock().unwrap().clone();
+        asked.sort();
+        asked.dedup();
+        assert_eq!(asked.len(), 3);
+        assert_eq!(*s.responses.lock().unwrap(), vec![(None, String::from("/root/router"))]);
+
+        s.system.terminate();
+    }
+}
@@ -0,0 +1,109 @@
+//! Constructor of the group routers
+//!
+//! Group router does not create routees, but uses existed actors specified by their paths. Paths
+//! is resolved at router start. Routees may be actors of the local system or actors of the remote
+//! system, if resolver is set. In the last case paths is also resolved periodically, and remote
+//! routees which is not resolved anymore is removed.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let props = Group::round_robin(vec!["/root/worker1", "/root/worker2"])
+//!     .props();
+//!
+//! let mut router = system.actor_of(props, Some("workers"));
+//! ```
+
+use crate::actors::props::Props;
+use crate::actors::actor_ref_factory::ActorRefFactory;
+use crate::actors::message::Message;
+use crate::actors::routing::router::{Router, DEFAULT_RESOLVE_INTERVAL};
+use crate::actors::routing::routing_logic::{RoutingLogic, RoundRobinRoutingLogic, RandomRoutingLogic,
+                                            BroadcastRoutingLogic, ConsistentHashingRoutingLogic};
+use crate::actors::routing::first_completed::{ScatterGatherFirstCompletedRoutingLogic, TailChoppingRoutingLogic};
+use crate::common::tsafe::TSafe;
+use std::sync::{Arc, Mutex};
+use std::hash::Hash;
+use std::time::Duration;
+
+pub struct Group {
+
+    /// Paths of the routees
+    paths: Vec<String>,
+
+    /// Routing logic of the router
+    logic: Box<RoutingLogic + Send>,
+
+    /// Factory through which paths will be resolved
+    resolver: Option<TSafe<ActorRefFactory + Send>>,
+
+    /// Interval of the paths resolving, if resolver is set
+    resolve_interval: Duration
+}
+
+impl Group {
+
+    /// Creates group with specified paths of routees and custom routing logic
+    pub fn new(paths: Vec<&str>, logic: Box<RoutingLogic + Send>) -> Group {
+        Group {
+            paths: paths.iter().map(|p| String::from(*p)).collect(),
+            logic,
+            resolver: None,
+            resolve_interval: DEFAULT_RESOLVE_INTERVAL
+        }
+    }
+
+    /// Creates group with round-robin routing logic
+    pub fn round_robin(paths: Vec<&str>) -> Group {
+        Group::new(paths, Box::new(RoundRobinRoutingLogic::new()))
+    }
+
+    /// Creates group with random routing logic
+    pub fn random(paths: Vec<&str>) -> Group {
+        Group::new(paths, Box::new(RandomRoutingLogic::new()))
+    }
+
+    /// Creates group which sends each message to all routees
+    pub fn broadcast(paths: Vec<&str>) -> Group {
+        Group::new(paths, Box::new(BroadcastRoutingLogic::new()))
+    }
+
+    /// Creates group which sends messages with the same key always to the same routee
+    pub fn consistent_hashing<K, F>(paths: Vec<&str>, virtual_nodes: usize, extractor: F) -> Group
+        where K: Hash, F: 'static + Fn(&Message) -> Option<K> + Send
+    {
+        Group::new(paths, Box::new(ConsistentHashingRoutingLogic::new(virtual_nodes, extractor)))
+    }
+
+    /// Creates group which sends each request to all routees and responds with the first answer
+    pub fn scatter_gather_first_completed(paths: Vec<&str>, within: Duration) -> Group {
+        Group::new(paths, Box::new(ScatterGatherFirstCompletedRoutingLogic::new(within)))
+    }
+
+    /// Creates group which sends each request to the next random routee through the interval, while
+    /// someone will not answer
+    pub fn tail_chopping(paths: Vec<&str>, within: Duration, interval: Duration) -> Group {
+        Group::new(paths, Box::new(TailChoppingRoutingLogic::new(within, interval)))
+    }
+
+    /// Sets factory through which paths will be resolved. It may be used for routing to the actors
+    /// of the remote system. By default paths is resolved in the router's actor system.
+    pub fn with_resolver(mut self, resolver: TSafe<ActorRefFactory + Send>) -> Group {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Sets interval through which paths is resolved again, if resolver is set. Remote routees
+    /// which is not resolved anymore, is removed from the routees list. 10 seconds by default.
+    pub fn with_resolve_interval(mut self, interval: Duration) -> Group {
+        self.resolve_interval = interval;
+        self
+    }
+
+    /// Creates props of the router
+    pub fn props(self) -> Props {
+        let router = Router::group(self.paths, self.logic, self.resolver)
+            .with_resolve_interval(self.resolve_interval);
+        Props::new(tsafe!(router))
+    }
+}
@@ -0,0 +1,11 @@
+//! Messages routing
+//!
+//! Router is the actor which forwards received messages to the set of other actors, which is
+//! named routees. See main actors doc for more details.
+
+pub mod routing_logic;
+pub mod router;
+pub mod first_completed;
+pub mod pool;
+pub mod group;
+pub mod resizer;
@@ -0,0 +1,111 @@
+//! Constructor of the pool routers
+//!
+//! Pool router creates routees itself, as his childs. All routees is created from props returned
+//! by the user defined factory.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let props = Pool::round_robin(5)
+//!     .props(|| Props::new(tsafe!(Worker::new())));
+//!
+//! let mut router = system.actor_of(props, Some("workers"));
+//! ```
+
+use crate::actors::props::Props;
+use crate::actors::routing::router::Router;
+use crate::actors::routing::resizer::Resizer;
+use crate::actors::routing::routing_logic::{RoutingLogic, RoundRobinRoutingLogic, RandomRoutingLogic,
+                                            SmallestMailboxRoutingLogic, BroadcastRoutingLogic,
+                                            ConsistentHashingRoutingLogic};
+use crate::actors::routing::first_completed::{ScatterGatherFirstCompletedRoutingLogic, TailChoppingRoutingLogic};
+use crate::actors::message::Message;
+use std::hash::Hash;
+use std::time::Duration;
+use std::sync::{Arc, Mutex};
+
+pub struct Pool {
+
+    /// Count of routees
+    size: usize,
+
+    /// Routing logic of the router
+    logic: Box<RoutingLogic + Send>,
+
+    /// Resizer of the pool
+    resizer: Option<Resizer>
+}
+
+impl Pool {
+
+    /// Creates pool with specified count of routees and custom routing logic
+    pub fn new(size: usize, logic: Box<RoutingLogic + Send>) -> Pool {
+        Pool {
+            size,
+            logic,
+            resizer: None
+        }
+    }
+
+    /// Creates pool with round-robin routing logic
+    pub fn round_robin(size: usize) -> Pool {
+        Pool::new(size, Box::new(RoundRobinRoutingLogic::new()))
+    }
+
+    /// Creates pool with random routing logic
+    pub fn random(size: usize) -> Pool {
+        Pool::new(size, Box::new(RandomRoutingLogic::new()))
+    }
+
+    /// Creates pool which sends messages to the routee with smallest mailbox
+    pub fn smallest_mailbox(size: usize) -> Pool {
+        Pool::new(size, Box::new(SmallestMailboxRoutingLogic::new()))
+    }
+
+    /// Creates pool which sends each message to all routees
+    pub fn broadcast(size: usize) -> Pool {
+        Pool::new(size, Box::new(BroadcastRoutingLogic::new()))
+    }
+
+    /// Creates pool which sends messages with the same key always to the same routee. Key is
+    /// extracted from a message by the specified function.
+    pub fn consistent_hashing<K, F>(size: usize, virtual_nodes: usize, extractor: F) -> Pool
+        where K: Hash, F: 'static + Fn(&Message) -> Option<K> + Send
+    {
+        Pool::new(size, Box::new(ConsistentHashingRoutingLogic::new(virtual_nodes, extractor)))
+    }
+
+    /// Creates pool which sends each request to all routees and responds to the sender with the
+    /// first received response. If no one routee will respond in the specified time, sender will
+    /// receive AskTimeoutError message.
+    pub fn scatter_gather_first_completed(size: usize, within: Duration) -> Pool {
+        Pool::new(size, Box::new(ScatterGatherFirstCompletedRoutingLogic::new(within)))
+    }
+
+    /// Creates pool which sends each request to the random routee, and after each interval without
+    /// response, to the next random routee. Sender receives the first response or AskTimeoutError
+    /// message, if no one routee will respond in the specified time.
+    pub fn tail_chopping(size: usize, within: Duration, interval: Duration) -> Pool {
+        Pool::new(size, Box::new(TailChoppingRoutingLogic::new(within, interval)))
+    }
+
+    /// Sets resizer which will change count of routees at runtime, based on their load. Initial
+    /// size of the pool is specified through the constructor.
+    pub fn with_resizer(mut self, resizer: Resizer) -> Pool {
+        self.resizer = Some(resizer);
+        self
+    }
+
+    /// Creates props of the router. Factory is called for each routee which will be created by the
+    /// router.
+    pub fn props<F>(self, factory: F) -> Props
+        where F: 'static + Fn() -> Props + Send
+    {
+        let mut router = Router::pool(self.size, self.logic, Box::new(factory));
+        if let Some(resizer) = self.resizer {
+            router = router.with_resizer(resizer);
+        }
+
+        Props::new(tsafe!(router))
+    }
+}
@@ -0,0 +1,214 @@
+//! Resizer of the pool routers
+//!
+//! Resizer periodically checks load of the routees and decides how much routees must be added to
+//! the pool or removed from it. With zero pressure threshold, routee is considered as busy, if he
+//! processes message at this moment or has messages in the mailbox. Otherwise, routee is busy if
+//! count of messages in his mailbox reaches the pressure threshold. Remote routees is not taken into
+//! account, because their mailboxes is not accessible. If all routees is busy, pool grows on the
+//! rampup rate. If ratio of the busy routees is less than backoff threshold, pool shrinks on the
+//! backoff rate. Count of routees always stays between lower and upper bounds.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let props = Pool::round_robin(2)
+//!     .with_resizer(Resizer::new(2, 16).set_interval(Duration::from_millis(500)))
+//!     .props(|| Props::new(tsafe!(Worker::new())));
+//! ```
+
+use crate::actors::abstract_actor_ref::ActorRef;
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct Resizer {
+
+    /// Minimum count of routees
+    pub lower_bound: usize,
+
+    /// Maximum count of routees
+    pub upper_bound: usize,
+
+    /// Count of messages in the mailbox at which routee is considered as busy. If zero, routee is
+    /// busy while his mailbox is planned for processing, including the message in flight.
+    pub pressure_threshold: usize,
+
+    /// Part of the current routees count which will be added if all routees is busy
+    pub rampup_rate: f64,
+
+    /// Ratio of the busy routees below which pool will be shrunk
+    pub backoff_threshold: f64,
+
+    /// Part of the current routees count which will be removed at backoff
+    pub backoff_rate: f64,
+
+    /// Interval between load checks
+    pub interval: Duration
+}
+
+impl Resizer {
+
+    /// Creates resizer with specified bounds. Pressure threshold = 0, rampup rate = 0.2, backoff
+    /// threshold = 0.3, backoff rate = 0.1, interval = 1 second.
+    pub fn new(lower_bound: usize, upper_bound: usize) -> Resizer {
+        if lower_bound == 0 || lower_bound > upper_bound {
+            panic!("Invalid bounds of the resizer - {}..{}", lower_bound, upper_bound);
+        }
+
+        Resizer {
+            lower_bound,
+            upper_bound,
+            pressure_threshold: 0,
+            rampup_rate: 0.2,
+            backoff_threshold: 0.3,
+            backoff_rate: 0.1,
+            interval: Duration::from_secs(1)
+        }
+    }
+
+    /// Set pressure threshold
+    pub fn set_pressure_threshold(mut self, threshold: usize) -> Self {
+        self.pressure_threshold = threshold;
+        self
+    }
+
+    /// Set rampup rate
+    pub fn set_rampup_rate(mut self, rate: f64) -> Self {
+        self.rampup_rate = rate;
+        self
+    }
+
+    /// Set backoff threshold
+    pub fn set_backoff_threshold(mut self, threshold: f64) -> Self {
+        self.backoff_threshold = threshold;
+        self
+    }
+
+    /// Set backoff rate
+    pub fn set_backoff_rate(mut self, rate: f64) -> Self {
+        self.backoff_rate = rate;
+        self
+    }
+
+    /// Set interval between load checks
+    pub fn set_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Calculates how much routees must be added (positive value) or removed (negative value)
+    /// from the pool, based on the mailboxes of the current routees. If pool has only remote
+    /// routees, his size is changed only to fit the bounds.
+    pub fn capacity(&self, routees: &Vec<ActorRef>) -> i64 {
+        let current = routees.len();
+
+        let mut local = 0;
+        let mut busy = 0;
+        for routee in routees.iter() {
+            if !routee.is_local() {
+                continue;
+            }
+            local = local + 1;
+
+            let is_busy = {
+                let cell = (*routee).clone().cell();
+                let cell = cell.lock().unwrap();
+                let mailbox = cell.mailbox.lock().unwrap();
+                if self.pressure_threshold == 0 {
+                    mailbox.is_planned() || mailbox.has_messages()
+                } else {
+                    mailbox.size() >= self.pressure_threshold
+                }
+            };
+
+            if is_busy {
+                busy = busy + 1;
+            }
+        }
+
+        let proposed = if current == 0 {
+            self.lower_bound
+        } else if local == 0 {
+            current
+        } else if busy == local {
+            current + (current as f64 * self.rampup_rate).ceil() as usize
+        } else if (busy as f64) / (local as f64) < self.backoff_threshold {
+            current - ((current as f64 * self.backoff_rate).ceil() as usize).min(current)
+        } else {
+            current
+        };
+
+        let proposed = proposed.max(self.lower_bound).min(self.upper_bound);
+
+        proposed as i64 - current as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::routing::resizer::Resizer;
+    use crate::actors::remoting::remote_actor_ref::RemoteActorRef;
+    use crate::actors::remoting::net_controller::NetController;
+    use crate::actors::actor_path::ActorPath;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use std::thread;
+
+    struct Sleeper {}
+
+    impl Actor for Sleeper {
+        fn receive(&mut self, _msg: Message, _ctx: ActorContext) -> HandleResult {
+            thread::sleep(Duration::from_millis(300));
+            Ok(true)
+        }
+    }
+
+    struct NoNet {}
+
+    impl NetController for NoNet {
+        fn send_msg(&mut self, _msg: Message, _rcid: u32, _rarid: u32, _sender: Option<ActorRef>, _far: ActorRef) {}
+    }
+
+    fn remote(name: &str) -> ActorRef {
+        let path = tsafe!(ActorPath::new(name, None));
+        Box::new(RemoteActorRef::new(0, 0, path, tsafe!(NoNet {})))
+    }
+
+    #[test]
+    fn routee_with_message_in_flight_is_busy() {
+        let mut system = LocalActorSystem::new();
+        let mut routee = system.actor_of(Props::new(tsafe!(Sleeper {})), None);
+        routee.tell(msg!(0), None);
+
+        // Message is taken from the mailbox, but is still processed
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(routee.cell().lock().unwrap().mailbox.lock().unwrap().size(), 0);
+
+        let resizer = Resizer::new(1, 4);
+        assert_eq!(resizer.capacity(&vec![routee.clone()]), 1);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn idle_pool_is_shrunk() {
+        let mut system = LocalActorSystem::new();
+        let routees: Vec<ActorRef> = (0..4)
+            .map(|_| system.actor_of(Props::new(tsafe!(Sleeper {})), None))
+            .collect();
+
+        let resizer = Resizer::new(1, 8);
+        assert_eq!(resizer.capacity(&routees), -1);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn remote_routees_is_skipped() {
+        let resizer = Resizer::new(1, 4);
+        assert_eq!(resizer.capacity(&vec![remote("r0"), remote("r1")]), 0);
+
+        let resizer = Resizer::new(3, 4);
+        assert_eq!(resizer.capacity(&vec![remote("r0")]), 2);
+    }
+}
@@ -0,0 +1,410 @@
+//! Router actor
+//!
+//! Router is an actor which does not process messages itself, but forwards they to the routees
+//! in accordance with the routing logic. Original sender of the message is preserved, so routee
+//! may respond directly to him.
+
+use crate::actors::actor::{Actor, HandleResult, PoisonPill};
+use crate::actors::actor_context::ActorContext;
+use crate::actors::actor_ref_factory::ActorRefFactory;
+use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::message::Message;
+use crate::actors::props::Props;
+use crate::common::tsafe::TSafe;
+use crate::actors::watcher::events::Terminated;
+use crate::actors::routing::routing_logic::RoutingLogic;
+use crate::actors::routing::resizer::Resizer;
+use crate::actors::timers::{Timers, RealTimers};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Constructor of the routee props. Called each time when router creates new routee.
+pub type RouteeFactory = Box<Fn() -> Props + Send>;
+
+/// Wrapper which forces router to send the inner message to all routees, independently of the
+/// routing logic.
+///
+/// # Example
+///
+/// ```ignore
+/// router.tell(msg!(Broadcast::new(msg!(commands::Reload {}))), None);
+/// ```
+pub struct Broadcast {
+    pub message: Message
+}
+
+impl Broadcast {
+    pub fn new(message: Message) -> Broadcast {
+        Broadcast {
+            message
+        }
+    }
+}
+
+/// Default interval of the routees paths resolving by the group router with resolver
+pub const DEFAULT_RESOLVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Message which initiates resizing of the pool
+struct ResizeTick {}
+
+/// Message which initiates resolving of the group routees paths
+struct ResolveTick {}
+
+pub struct Router {
+
+    /// Routing logic
+    logic: Box<RoutingLogic + Send>,
+
+    /// Current routees list
+    routees: Vec<ActorRef>,
+
+    /// Count of routees which will be created at router start
+    size: usize,
+
+    /// Constructor of the routees. Exists only in the pool routers.
+    factory: Option<RouteeFactory>,
+
+    /// Paths of the existed actors which is used as routees. Exists only in the group routers.
+    paths: Vec<String>,
+
+    /// Factory through which routees paths is resolved. If it is not set, paths is resolved
+    /// through the router's context.
+    resolver: Option<TSafe<ActorRefFactory + Send>>,
+
+    /// Resizer of the pool
+    resizer: Option<Resizer>,
+
+    /// Interval of the paths resolving for the group with resolver
+    resolve_interval: Duration,
+
+    /// Timers of the resizer and the resolving
+    timers: Option<Timers>
+}
+
+impl Router {
+
+    /// Creates router which at start creates specified count of routees as his childs. If all
+    /// routees will be terminated, router will be stopped too.
+    pub fn pool(size: usize, logic: Box<RoutingLogic + Send>, factory: RouteeFactory) -> Router {
+        Router {
+            logic,
+            routees: Vec::new(),
+            size,
+            factory: Some(factory),
+            paths: Vec::new(),
+            resolver: None,
+            resizer: None,
+            resolve_interval: DEFAULT_RESOLVE_INTERVAL,
+            timers: None
+        }
+    }
+
+    /// Creates router which at start resolves routees from the specified paths of the existed
+    /// actors. Paths is resolved through the actor_select call of the specified factory, or of the
+    /// router's context if factory is not set. So this router may route messages to the actors of
+    /// the remote system. Router watches his routees and removes terminated routees from the list.
+    /// If factory is set, paths is also resolved periodically, because remote actors is not watched
+    /// across the network. Remote routees which is not resolved anymore, is removed from the list.
+    pub fn group(paths: Vec<String>, logic: Box<RoutingLogic + Send>, resolver: Option<TSafe<ActorRefFactory + Send>>) -> Router {
+        Router {
+            logic,
+            routees: Vec::new(),
+            size: 0,
+            factory: None,
+            paths,
+            resolver,
+            resizer: None,
+            resolve_interval: DEFAULT_RESOLVE_INTERVAL,
+            timers: None
+        }
+    }
+
+    /// Sets resizer of the pool. Has effect only for pool routers.
+    pub fn with_resizer(mut self, resizer: Resizer) -> Router {
+        self.resizer = Some(resizer);
+        self
+    }
+
+    /// Sets interval of the paths resolving. Has effect only for group routers with resolver.
+    pub fn with_resolve_interval(mut self, interval: Duration) -> Router {
+        self.resolve_interval = interval;
+        self
+    }
+
+    /// Returns current routees list
+    pub fn routees(&self) -> &Vec<ActorRef> {
+        &self.routees
+    }
+
+    /// Creates new routee and adds it to the routees list
+    fn add_routee(&mut self, ctx: &mut ActorContext) {
+        if let Some(factory) = self.factory.as_ref() {
+            let self_ = ctx.self_.clone();
+            let routee = ctx.actor_of(factory(), None);
+            ctx.system().watch(&self_, &routee);
+            self.routees.push(routee);
+        }
+    }
+
+    /// Changes count of routees in accordance with the resizer decision. Removed routees is
+    /// stopped by the PoisonPill after they process messages from their mailboxes.
+    fn resize(&mut self, ctx: &mut ActorContext) {
+        let capacity = match self.resizer.as_ref() {
+            Some(resizer) => resizer.capacity(&self.routees),
+            None => 0
+        };
+
+        if capacity > 0 {
+            for _ in 0..capacity {
+                self.add_routee(ctx);
+            }
+        } else if capacity < 0 {
+            for _ in 0..(-capacity) {
+                let mut routee = self.routees.pop().unwrap();
+                ctx.system().unwatch(&ctx.self_, &routee);
+                routee.tell(msg!(PoisonPill {}), Some(&ctx.self_));
+            }
+        }
+
+        if capacity != 0 {
+            debug!("Router {} resized to {} routees", ctx.self_, self.routees.len());
+        }
+    }
+
+    /// Resolves path through the resolver or through the router's context
+    fn select(&self, path: &str, ctx: &mut ActorContext) -> Vec<ActorRef> {
+        match self.resolver.as_ref() {
+            Some(resolver) => resolver.lock().unwrap().actor_select(path),
+            None => ctx.actor_select(path)
+        }
+    }
+
+    /// Resolves paths of the group again. Remote routees which is not resolved anymore is removed
+    /// from the list. Routees which was resolved first time, is added to the list.
+    fn resolve(&mut self, ctx: &mut ActorContext) {
+        let self_ = ctx.self_.clone();
+
+        let mut resolved = Vec::new();
+        for path in self.paths.iter() {
+            resolved.append(&mut self.select(path, ctx));
+        }
+
+        let before = self.routees.len();
+        self.routees.retain(|r| r.is_local() || resolved.iter().any(|n| n.path() == r.path()));
+        let removed = before - self.routees.len();
+
+        let mut added = 0;
+        for routee in resolved {
+            if !self.routees.iter().any(|r| r.path() == routee.path()) {
+                ctx.system().watch(&self_, &routee);
+                self.routees.push(routee);
+                added = added + 1;
+            }
+        }
+
+        if removed > 0 || added > 0 {
+            debug!("Router {} re-resolved routees, {} removed, {} added", self_, removed, added);
+        }
+    }
+
+    /// Sends the message to all routees
+    fn broadcast(&mut self, msg: Message, sender: &ActorRef) {
+        for routee in self.routees.iter_mut() {
+            routee.tell(msg.clone(), Some(sender));
+        }
+    }
+}
+
+impl Actor for Router {
+
+    fn pre_start(&mut self, mut ctx: ActorContext) {
+        self.routees.clear();
+
+        let self_ = ctx.self_.clone();
+
+        for _ in 0..self.size {
+            self.add_routee(&mut ctx);
+        }
+
+        if self.factory.is_some() {
+            if let Some(resizer) = self.resizer.as_ref() {
+                let mut timers = RealTimers::new(ctx.system.clone());
+                timers.start_periodic(0, &self_, &self_, resizer.interval, Box::new(|| msg!(ResizeTick {})));
+                self.timers = Some(timers);
+            }
+        }
+
+        if self.factory.is_none() && self.resolver.is_some() {
+            let mut timers = RealTimers::new(ctx.system.clone());
+            timers.start_periodic(1, &self_, &self_, self.resolve_interval, Box::new(|| msg!(ResolveTick {})));
+            self.timers = Some(timers);
+        }
+
+        for path in self.paths.iter() {
+            let selection = self.select(path, &mut ctx);
+
+            if selection.len() == 0 {
+                warn!("Router {} unable to resolve routee '{}'", self_, path);
+            }
+
+            for routee in selection {
+                ctx.system().watch(&self_, &routee);
+                self.routees.push(routee);
+            }
+        }
+    }
+
+    fn post_stop(&mut self, ctx: ActorContext) {
+        if let Some(timers) = self.timers.as_mut() {
+            timers.cancel_all();
+        }
+
+        for routee in self.routees.iter() {
+            ctx.system().unwatch(&ctx.self_, routee);
+        }
+    }
+
+    fn receive(&mut self, msg: Message, mut ctx: ActorContext) -> HandleResult {
+        let broadcast = msg.get().downcast_ref::<Broadcast>().map(|b| b.message.clone());
+        if let Some(inner) = broadcast {
+            self.broadcast(inner, &ctx.sender);
+            return Ok(true);
+        }
+
+        // Routees of the pool is stopped by the PoisonPill after they process all previous
+        // messages. Router will be stopped after the last routee will be terminated. Routees of
+        // the group is not owned by the router, so only router itself is stopped.
+        if msg.get().is::<PoisonPill>() {
+            if self.factory.is_some() && self.routees.len() > 0 {
+                self.broadcast(msg, &ctx.sender);
+                return Ok(true);
+            } else {
+                return Ok(false);
+            }
+        }
+
+        if msg.get().is::<ResizeTick>() {
+            self.resize(&mut ctx);
+            return Ok(true);
+        }
+
+        if msg.get().is::<ResolveTick>() {
+            self.resolve(&mut ctx);
+            return Ok(true);
+        }
+
+        if msg.get().is::<Terminated>() {
+            let path = ctx.sender.path();
+            let before = self.routees.len();
+            self.routees.retain(|r| r.path() != path);
+
+            if before > 0 && self.routees.len() == 0 {
+                ctx.self_.tell(msg!(PoisonPill {}), None);
+            }
+
+            return Ok(true);
+        }
+
+        if !self.logic.route(msg, &mut ctx, &self.routees) {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::actor_ref_factory::ActorRefFactory;
+    use crate::actors::actor_path::ActorPath;
+    use crate::common::tsafe::TSafe;
+    use crate::actors::remoting::remote_actor_ref::RemoteActorRef;
+    use crate::actors::remoting::net_controller::NetController;
+    use crate::actors::routing::group::Group;
+    use crate::actors::routing::router::Broadcast;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    /// Records ids of the remote actors which received messages
+    struct RecordingNet {
+        sent: Arc<Mutex<Vec<u32>>>
+    }
+
+    impl NetController for RecordingNet {
+        fn send_msg(&mut self, _msg: Message, _rcid: u32, rarid: u32, _sender: Option<ActorRef>, _far: ActorRef) {
+            self.sent.lock().unwrap().push(rarid);
+        }
+    }
+
+    /// Resolves only paths of the available remote actors
+    struct StubResolver {
+        available: Arc<Mutex<Vec<u32>>>,
+        net: TSafe<NetController + Send>
+    }
+
+    impl ActorRefFactory for StubResolver {
+        fn actor_of(self: &mut Self, _props: Props, _name: Option<&str>) -> ActorRef { unimplemented!() }
+
+        fn actor_select(&mut self, path: &str) -> Vec<ActorRef> {
+            let id: u32 = path.trim_start_matches("/root/worker").parse().unwrap();
+            if self.available.lock().unwrap().contains(&id) {
+                let path = tsafe!(ActorPath::new(&format!("worker{}", id), None));
+                vec![Box::new(RemoteActorRef::new(0, id, path, self.net.clone()))]
+            } else {
+                Vec::new()
+            }
+        }
+
+        fn stop(self: &mut Self, _aref: &mut ActorRef) { unimplemented!() }
+        fn dead_letters(self: &mut Self) -> ActorRef { unimplemented!() }
+        fn watch(&mut self, _watcher: &ActorRef, _observed: &ActorRef) {}
+        fn unwatch(&mut self, _watcher: &ActorRef, _observed: &ActorRef) {}
+    }
+
+    fn broadcast_and_collect(router: &mut ActorRef, sent: &Arc<Mutex<Vec<u32>>>, count: usize) -> Vec<u32> {
+        sent.lock().unwrap().clear();
+        router.tell(msg!(Broadcast::new(msg!(0))), None);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while sent.lock().unwrap().len() < count && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(Duration::from_millis(50));
+
+        let mut sent = sent.lock().unwrap().clone();
+        sent.sort();
+        sent
+    }
+
+    #[test]
+    fn group_drops_remote_routees_which_is_not_resolved_anymore() {
+        let mut system = LocalActorSystem::new();
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let available = Arc::new(Mutex::new(vec![1, 2]));
+        let resolver = StubResolver {
+            available: available.clone(),
+            net: tsafe!(RecordingNet { sent: sent.clone() })
+        };
+
+        let props = Group::broadcast(vec!["/root/worker1", "/root/worker2"])
+            .with_resolver(tsafe!(resolver))
+            .with_resolve_interval(Duration::from_millis(50))
+            .props();
+        let mut router = system.actor_of(props, Some("router"));
+
+        assert_eq!(broadcast_and_collect(&mut router, &sent, 2), vec![1, 2]);
+
+        available.lock().unwrap().retain(|id| *id != 2);
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(broadcast_and_collect(&mut router, &sent, 1), vec![1]);
+
+        // Routee is added back, when it may be resolved again
+        available.lock().unwrap().push(2);
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(broadcast_and_collect(&mut router, &sent, 2), vec![1, 2]);
+
+        system.terminate();
+    }
+}
@@ -0,0 +1,413 @@
+//! Routing logics
+//!
+//! Routing logic decides to which routees the message will be sent by a router. Logic receives
+//! the message and the current list of routees, and returns routees which must receive this
+//! message. This list may be empty, in this case message will be dropped to the deadLetter.
+
+use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::actor_context::ActorContext;
+use crate::actors::remoting::remote_actor_ref::RemoteActorRef;
+use crate::actors::message::Message;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+pub trait RoutingLogic {
+
+    /// Selects routees which must receive the message
+    fn select(&mut self, msg: &Message, routees: &Vec<ActorRef>) -> Vec<ActorRef>;
+
+    /// Sends the message to the selected routees on behalf of the original sender. Logics which
+    /// needs to process responses of the routees, may override this method. Returns false if
+    /// message was not sent to anyone.
+    fn route(&mut self, msg: Message, ctx: &mut ActorContext, routees: &Vec<ActorRef>) -> bool {
+        let targets = self.select(&msg, routees);
+        if targets.len() == 0 {
+            return false;
+        }
+
+        for mut target in targets {
+            target.tell(msg.clone(), Some(&ctx.sender));
+        }
+
+        true
+    }
+}
+
+/// Sends messages to the routees one by one in the cycle
+pub struct RoundRobinRoutingLogic {
+    next: usize
+}
+
+impl RoundRobinRoutingLogic {
+    pub fn new() -> RoundRobinRoutingLogic {
+        RoundRobinRoutingLogic {
+            next: 0
+        }
+    }
+}
+
+impl RoutingLogic for RoundRobinRoutingLogic {
+    fn select(&mut self, _msg: &Message, routees: &Vec<ActorRef>) -> Vec<ActorRef> {
+        if routees.len() == 0 {
+            return Vec::new();
+        }
+
+        let index = self.next % routees.len();
+        self.next = index + 1;
+
+        vec![routees[index].clone()]
+    }
+}
+
+/// Sends each message to the random routee
+pub struct RandomRoutingLogic {}
+
+impl RandomRoutingLogic {
+    pub fn new() -> RandomRoutingLogic {
+        RandomRoutingLogic {}
+    }
+}
+
+impl RoutingLogic for RandomRoutingLogic {
+    fn select(&mut self, _msg: &Message, routees: &Vec<ActorRef>) -> Vec<ActorRef> {
+        if routees.len() == 0 {
+            return Vec::new();
+        }
+
+        let index = rand::thread_rng().gen_range(0, routees.len());
+
+        vec![routees[index].clone()]
+    }
+}
+
+/// Sends each message to the routee which have fewest count of messages in the mailbox. If few
+/// routees have the same count of messages, first of them is selected. Mailbox of a remote actor
+/// is not accessible, so remote routees have the lowest priority and is selected only if there is
+/// no local routees.
+pub struct SmallestMailboxRoutingLogic {}
+
+impl SmallestMailboxRoutingLogic {
+    pub fn new() -> SmallestMailboxRoutingLogic {
+        SmallestMailboxRoutingLogic {}
+    }
+}
+
+impl RoutingLogic for SmallestMailboxRoutingLogic {
+    fn select(&mut self, _msg: &Message, routees: &Vec<ActorRef>) -> Vec<ActorRef> {
+        let mut selected: Option<&ActorRef> = None;
+        let mut min = usize::max_value();
+
+        for routee in routees.iter() {
+            let size = if routee.is_local() {
+                let cell = (*routee).clone().cell();
+                let cell = cell.lock().unwrap();
+                let size = cell.mailbox.lock().unwrap().size();
+                size
+            } else {
+                usize::max_value()
+            };
+
+            if selected.is_none() || size < min {
+                min = size;
+                selected = Some(routee);
+            }
+
+            if min == 0 {
+                break;
+            }
+        }
+
+        match selected {
+            Some(routee) => vec![(*routee).clone()],
+            None => Vec::new()
+        }
+    }
+}
+
+/// Sends each message to all routees
+pub struct BroadcastRoutingLogic {}
+
+impl BroadcastRoutingLogic {
+    pub fn new() -> BroadcastRoutingLogic {
+        BroadcastRoutingLogic {}
+    }
+}
+
+impl RoutingLogic for BroadcastRoutingLogic {
+    fn select(&mut self, _msg: &Message, routees: &Vec<ActorRef>) -> Vec<ActorRef> {
+        routees.iter().map(|r| (*r).clone()).collect()
+    }
+}
+
+/// Function which extracts hash of the key from a message
+pub type HashExtractor = Box<Fn(&Message) -> Option<u64> + Send>;
+
+/// Sends messages with the same key always to the same routee. Key is extracted from a message by
+/// the user defined function. Messages for which key was not extracted, is dropped to the
+/// deadLetter. Each routee is placed to the hash ring few times (virtual nodes), so keys is
+/// distributed between routees evenly. When routees list is changed, only keys of the added or
+/// removed routees will be moved to other routees.
+pub struct ConsistentHashingRoutingLogic {
+
+    /// Count of virtual nodes of each routee on the ring
+    virtual_nodes: usize,
+
+    /// Key hash extractor
+    extractor: HashExtractor,
+
+    /// Hash ring. Value is the index of the routee in the routees list.
+    ring: BTreeMap<u64, usize>,
+
+    /// Keys of the routees for which ring was built
+    members: Vec<String>
+}
+
+impl ConsistentHashingRoutingLogic {
+
+    /// Creates new logic with specified count of virtual nodes per routee and key extractor
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// ConsistentHashingRoutingLogic::new(100, |msg: &Message| {
+    ///     msg.get().downcast_ref::<commands::Order>().map(|m| m.customer_id)
+    /// });
+    /// ```
+    pub fn new<K, F>(virtual_nodes: usize, extractor: F) -> ConsistentHashingRoutingLogic
+        where K: Hash, F: 'static + Fn(&Message) -> Option<K> + Send
+    {
+        if virtual_nodes == 0 {
+            panic!("Count of virtual nodes must be greater than zero");
+        }
+
+        ConsistentHashingRoutingLogic {
+            virtual_nodes,
+            extractor: Box::new(move |msg| extractor(msg).map(|k| hash_of(&k))),
+            ring: BTreeMap::new(),
+            members: Vec::new()
+        }
+    }
+
+    /// Rebuilds the ring if routees list was changed since last build
+    fn update_ring(&mut self, routees: &Vec<ActorRef>) {
+        let keys: Vec<String> = routees.iter().map(|r| routee_key(r)).collect();
+        if keys == self.members {
+            return;
+        }
+
+        self.ring.clear();
+        for (index, key) in keys.iter().enumerate() {
+            for node in 0..self.virtual_nodes {
+                self.ring.insert(hash_of(&(key, node)), index);
+            }
+        }
+
+        self.members = keys;
+    }
+}
+
+impl RoutingLogic for ConsistentHashingRoutingLogic {
+    fn select(&mut self, msg: &Message, routees: &Vec<ActorRef>) -> Vec<ActorRef> {
+        if routees.len() == 0 {
+            return Vec::new();
+        }
+
+        let key = match (self.extractor)(msg) {
+            Some(key) => key,
+            None => return Vec::new()
+        };
+
+        self.update_ring(routees);
+
+        let node = self.ring.range(key..).next()
+            .or_else(|| self.ring.iter().next());
+
+        match node {
+            Some((_, index)) => vec![routees[*index].clone()],
+            None => Vec::new()
+        }
+    }
+}
+
+/// Identifies the routee on the ring. This is the full path of the actor, and for the remote actor
+/// also the id of the connection through which he is accessible, because actors with the same
+/// path may exist in the different systems.
+fn routee_key(routee: &ActorRef) -> String {
+    if !routee.is_local() {
+        if let Some(remote) = routee.as_any().downcast_ref::<Box<RemoteActorRef>>() {
+            return format!("{}@{}", remote.path.lock().unwrap(), remote.rcid);
+        }
+    }
+
+    routee.path().to_string()
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::routing::routing_logic::{RoutingLogic, RoundRobinRoutingLogic, SmallestMailboxRoutingLogic, BroadcastRoutingLogic, ConsistentHashingRoutingLogic};
+    use crate::actors::remoting::remote_actor_ref::RemoteActorRef;
+    use crate::actors::remoting::net_controller::NetController;
+    use crate::actors::actor_path::ActorPath;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use std::thread;
+
+    struct Sleeper {}
+
+    impl Actor for Sleeper {
+        fn receive(&mut self, _msg: Message, _ctx: ActorContext) -> HandleResult {
+            thread::sleep(Duration::from_millis(100));
+            Ok(true)
+        }
+    }
+
+    struct NoNet {}
+
+    impl NetController for NoNet {
+        fn send_msg(&mut self, _msg: Message, _rcid: u32, _rarid: u32, _sender: Option<ActorRef>, _far: ActorRef) {}
+    }
+
+    fn remote(name: &str) -> ActorRef {
+        let path = tsafe!(ActorPath::new(name, None));
+        Box::new(RemoteActorRef::new(0, 0, path, tsafe!(NoNet {})))
+    }
+
+    /// Remote routee with the specified parent path and connection id
+    fn remote_at(rcid: u32, parent: &str, name: &str) -> ActorRef {
+        let parent = tsafe!(ActorPath::new(parent, None));
+        let path = tsafe!(ActorPath::new(name, Some(parent)));
+        Box::new(RemoteActorRef::new(rcid, 0, path, tsafe!(NoNet {})))
+    }
+
+    fn hashing() -> ConsistentHashingRoutingLogic {
+        ConsistentHashingRoutingLogic::new(50, |msg: &Message| msg.get().downcast_ref::<u32>().map(|k| *k))
+    }
+
+    /// Index of the routee selected for each key
+    fn owners(logic: &mut ConsistentHashingRoutingLogic, routees: &Vec<ActorRef>) -> Vec<usize> {
+        (0..1000 as u32).map(|key| {
+            let selected = logic.select(&msg!(key), routees);
+            routees.iter().position(|r| address(r) == address(&selected[0])).unwrap()
+        }).collect()
+    }
+
+    fn address(routee: &ActorRef) -> String {
+        let remote = routee.as_any().downcast::<Box<RemoteActorRef>>().ok().unwrap();
+        format!("{}@{}", remote.path.lock().unwrap(), remote.rcid)
+    }
+
+    fn names(routees: Vec<ActorRef>) -> Vec<String> {
+        routees.iter().map(|r| r.path().name).collect()
+    }
+
+    #[test]
+    fn round_robin_selects_routees_in_turn() {
+        let routees = vec![remote("a"), remote("b"), remote("c")];
+        let mut logic = RoundRobinRoutingLogic::new();
+        let mut selected = Vec::new();
+        for _ in 0..4 {
+            selected.append(&mut names(logic.select(&msg!(0), &routees)));
+        }
+
+        assert_eq!(selected.len(), 4);
+        assert_eq!(selected[0], selected[3]);
+        assert_ne!(selected[0], selected[1]);
+        assert_ne!(selected[1], selected[2]);
+    }
+
+    #[test]
+    fn broadcast_selects_all_routees() {
+        let routees = vec![remote("a"), remote("b")];
+        let mut logic = BroadcastRoutingLogic::new();
+        assert_eq!(names(logic.select(&msg!(0), &routees)), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn smallest_mailbox_selects_idle_local_routee() {
+        let mut system = LocalActorSystem::new();
+        let mut busy = system.actor_of(Props::new(tsafe!(Sleeper {})), Some("busy"));
+        let idle = system.actor_of(Props::new(tsafe!(Sleeper {})), Some("idle"));
+        for _ in 0..5 {
+            busy.tell(msg!(0), None);
+        }
+
+        let routees = vec![busy.clone(), idle.clone()];
+        let mut logic = SmallestMailboxRoutingLogic::new();
+        assert_eq!(names(logic.select(&msg!(0), &routees)), vec!["idle"]);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn smallest_mailbox_gives_lowest_priority_to_remote_routees() {
+        let mut system = LocalActorSystem::new();
+        let local = system.actor_of(Props::new(tsafe!(Sleeper {})), Some("local"));
+
+        let mut logic = SmallestMailboxRoutingLogic::new();
+        let routees = vec![remote("remote"), local.clone()];
+        assert_eq!(names(logic.select(&msg!(0), &routees)), vec!["local"]);
+
+        let routees = vec![remote("r0"), remote("r1")];
+        assert_eq!(names(logic.select(&msg!(0), &routees)), vec!["r0"]);
+
+        system.terminate();
+    }
+
+    #[test]
+    fn consistent_hashing_maps_keys_stably() {
+        let routees = vec![remote("a"), remote("b"), remote("c")];
+        let first = owners(&mut hashing(), &routees);
+        let second = owners(&mut hashing(), &routees);
+
+        assert_eq!(first, second);
+        for index in 0..3 {
+            assert!(first.iter().any(|o| *o == index));
+        }
+        assert_eq!(hashing().select(&msg!(String::from("no key")), &routees).len(), 0);
+    }
+
+    #[test]
+    fn consistent_hashing_distinguishes_routees_with_the_same_name() {
+        let routees = vec![remote_at(0, "a", "worker"), remote_at(0, "b", "worker"), remote_at(1, "a", "worker")];
+        let owners = owners(&mut hashing(), &routees);
+
+        for index in 0..3 {
+            assert!(owners.iter().filter(|o| **o == index).count() > 100);
+        }
+    }
+
+    #[test]
+    fn consistent_hashing_moves_only_keys_of_changed_routees() {
+        let mut logic = hashing();
+        let routees = vec![remote("a"), remote("b"), remote("c")];
+        let before = owners(&mut logic, &routees);
+
+        // Added routee takes keys only from others, other keys stay in place
+        let mut added = vec![remote("a"), remote("b"), remote("c"), remote("d")];
+        let after = owners(&mut logic, &added);
+        for (old, new) in before.iter().zip(after.iter()) {
+            assert!(old == new || *new == 3);
+        }
+        assert!(after.iter().any(|o| *o == 3));
+
+        // Keys of the removed routee is moved to others, other keys stay in place
+        added.remove(1);
+        let removed = owners(&mut logic, &added);
+        let names = ["a", "b", "c", "d"];
+        let remaining = ["a", "c", "d"];
+        for (old, new) in after.iter().zip(removed.iter()) {
+            if *old != 1 {
+                assert_eq!(names[*old], remaining[*new]);
+            }
+        }
+    }
+}
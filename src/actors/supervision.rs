@@ -1,9 +1,103 @@
 //! Structs used for supervision
 
+use crate::actors::error::Error;
+use std::time::Duration;
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum SupervisionStrategy {
     Resume,
     Stop,
     Restart,
     Escalate
-}
\ No newline at end of file
+}
+
+/// Limit of restarts of an actor. If the actor restarts more than max_restarts times within the
+/// specified duration, instead of next restart the exceed strategy will be applied to him.
+#[derive(Clone, Debug)]
+pub struct RestartLimit {
+
+    /// Maximum count of restarts within the time window
+    pub max_restarts: u32,
+
+    /// Duration of the time window
+    pub within: Duration,
+
+    /// Supervision action which is applied when the limit is exceeded. Stop by default.
+    pub exceed_strategy: SupervisionStrategy
+}
+
+impl RestartLimit {
+    pub fn new(max_restarts: u32, within: Duration) -> RestartLimit {
+        RestartLimit {
+            max_restarts,
+            within,
+            exceed_strategy: SupervisionStrategy::Stop
+        }
+    }
+
+    /// Sets action which is applied when the limit is exceeded. This may be Stop, Escalate or
+    /// Resume.
+    pub fn set_exceed_strategy(mut self, strategy: SupervisionStrategy) -> RestartLimit {
+        if strategy == SupervisionStrategy::Restart {
+            panic!("Restart can not be used as exceed strategy of the restart limit");
+        }
+        self.exceed_strategy = strategy;
+        self
+    }
+}
+
+/// Command which is sent to the siblings of the failed actor, when all-for-one strategy is used.
+/// It's processed by the dispatcher and is never passed to the actor.
+pub struct SiblingFailed {
+
+    /// Action decided by the supervisor strategy
+    pub strategy: SupervisionStrategy,
+
+    /// Failure of the sibling
+    pub err: Error
+}
+
+/// Function which decides, what supervision action must be applied to the failed child
+pub type Decider = Box<Fn(&Error) -> SupervisionStrategy + Send>;
+
+/// Strategy with which the parent actor supervises his childs. Set up through the parent's props.
+/// If parent does not have this strategy, failed child uses supervision strategy from his own props.
+pub enum SupervisorStrategy {
+
+    /// Decided action is applied only to the failed child
+    OneForOne(Decider),
+
+    /// Decided action is applied to the failed child and to all his siblings
+    AllForOne(Decider)
+}
+
+impl SupervisorStrategy {
+
+    /// Creates one-for-one strategy with the specified decider
+    pub fn one_for_one<F>(decider: F) -> SupervisorStrategy
+        where F: Fn(&Error) -> SupervisionStrategy + Send + 'static {
+        SupervisorStrategy::OneForOne(Box::new(decider))
+    }
+
+    /// Creates all-for-one strategy with the specified decider
+    pub fn all_for_one<F>(decider: F) -> SupervisorStrategy
+        where F: Fn(&Error) -> SupervisionStrategy + Send + 'static {
+        SupervisorStrategy::AllForOne(Box::new(decider))
+    }
+
+    /// Returns supervision action for the specified error
+    pub fn decide(&self, err: &Error) -> SupervisionStrategy {
+        match self {
+            SupervisorStrategy::OneForOne(decider) => decider(err),
+            SupervisorStrategy::AllForOne(decider) => decider(err)
+        }
+    }
+
+    /// Is action must be applied to all childs of the supervisor
+    pub fn is_all_for_one(&self) -> bool {
+        match self {
+            SupervisorStrategy::OneForOne(_) => false,
+            SupervisorStrategy::AllForOne(_) => true
+        }
+    }
+}
@@ -48,6 +48,10 @@ impl Mailbox for UnboundMailbox {
         self.queue.len() > 0
     }
 
+    fn size(self: &Self) -> usize {
+        self.queue.len()
+    }
+
     /// Drops all messages to the DeadLetter
     fn clean_up(self: &mut Self, sender: ActorRef, dead_letters: ActorRef) {
         let mut dead_letters = dead_letters;
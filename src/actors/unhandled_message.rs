@@ -0,0 +1,127 @@
+//! Unhandled messages
+//!
+//! If the actor returns Ok(false) from the receive function, message is considered as unhandled.
+//! For each such message, UnhandledMessage event is published to the event stream of the actor
+//! system. If it's enabled in the actor system, event also is logged with warning level. Logging is
+//! disabled by default.
+//!
+
+use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::abstract_actor_system::AbstractActorSystem;
+use crate::actors::message::Message;
+use crate::common::tsafe::TSafe;
+use std::sync::{Arc, Mutex};
+
+/// Event which is published to the event stream for each message, which was not handled by the
+/// actor
+pub struct UnhandledMessage {
+
+    /// Unhandled message
+    pub message: Message,
+
+    /// Who send this message. None if message was sent from outside of the actor system.
+    pub sender: Option<ActorRef>,
+
+    /// Actor which does not handle the message
+    pub recipient: ActorRef
+}
+
+impl UnhandledMessage {
+
+    /// Logs the event if it's enabled and publish it to the event stream of the actor system. Must
+    /// be called only when no one actor's lock is held by the current thread, because event is
+    /// sent to subscribers directly.
+    pub fn publish(system: &TSafe<AbstractActorSystem + Send>, message: Message, sender: ActorRef, recipient: ActorRef) {
+        let (event_stream, log_unhandled, dead_letters_path) = {
+            let mut system = system.lock().unwrap();
+            (system.event_stream(), system.is_log_unhandled(), system.dead_letters().path())
+        };
+
+        let sender = if sender.path() == dead_letters_path { None } else { Some(sender) };
+
+        if log_unhandled {
+            let actor_name = match sender.as_ref() {
+                Some(sender) => sender.to_string(),
+                None => "outside".to_string()
+            };
+            warn!("Actor {} does not handle some message from '{}'", recipient, actor_name);
+        }
+
+        let event = UnhandledMessage {
+            message,
+            sender,
+            recipient
+        };
+
+        event_stream.publish(msg!(event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    struct Deaf;
+
+    impl Actor for Deaf {
+        fn receive(&mut self, _msg: Message, _ctx: ActorContext) -> HandleResult {
+            Ok(false)
+        }
+    }
+
+    /// Sends message to the target on behalf of his child named as the system dead letters
+    struct Parent {
+        target: ActorRef
+    }
+
+    impl Actor for Parent {
+        fn pre_start(&mut self, mut ctx: ActorContext) {
+            let child = ctx.actor_of(Props::new(tsafe!(Deaf)), Some("deadLetters"));
+            self.target.tell(msg!(2 as u32), Some(&child));
+        }
+
+        fn receive(&mut self, _msg: Message, _ctx: ActorContext) -> HandleResult {
+            Ok(true)
+        }
+    }
+
+    struct Listener {
+        senders: Arc<Mutex<Vec<Option<String>>>>
+    }
+
+    impl Actor for Listener {
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<UnhandledMessage>() {
+                Some(event) => self.senders.lock().unwrap().push(event.sender.as_ref().map(|s| s.path().to_string())),
+                None => return Ok(false)
+            }
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn only_system_dead_letters_is_considered_as_outside_sender() {
+        let mut system = LocalActorSystem::new();
+        assert!(!system.is_log_unhandled());
+
+        let senders = Arc::new(Mutex::new(Vec::new()));
+        let listener = system.actor_of(Props::new(tsafe!(Listener { senders: senders.clone() })), None);
+        system.event_stream().subscribe::<UnhandledMessage>(&listener);
+
+        let mut deaf = system.actor_of(Props::new(tsafe!(Deaf)), None);
+        deaf.tell(msg!(1 as u32), None);
+        let _parent = system.actor_of(Props::new(tsafe!(Parent { target: deaf.clone() })), Some("parent"));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while senders.lock().unwrap().len() < 2 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(*senders.lock().unwrap(), vec![None, Some(String::from("/root/parent/deadLetters"))]);
+
+        system.terminate();
+    }
+}
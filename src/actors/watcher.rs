@@ -6,27 +6,106 @@
 use crate::actors::abstract_actor_ref::ActorRef;
 use crate::actors::actor_path::ActorPath;
 use crate::actors::message::Message;
+use crate::actors::error::Error;
+use crate::actors::supervision::SupervisionStrategy;
+use crate::actors::event_stream::EventStream;
 use std::collections::HashMap;
 use std::sync::{Mutex, Arc};
 
 pub enum WatchingEvents {
-    Terminated
+    Terminated(StopReason),
+    Restarted(Error),
+    Failed(Error, SupervisionStrategy)
+}
+
+/// Reason by which the actor was stopped
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum StopReason {
+
+    /// Actor was stopped through the stop call, or as a child of stopped actor
+    Normal,
+
+    /// Actor was stopped by the PoisonPill message
+    PoisonPill,
+
+    /// Actor was stopped by the supervision after a failure
+    Supervision,
+
+    /// Actor was stopped because the actor system was terminated
+    SystemShutdown
+}
+
+/// Error which is returned by watch_lifecycle, if the actor system is unable to deliver Failed and
+/// Restarted events of the observed actor
+#[derive(Clone, Debug)]
+pub struct LifecycleWatchError {
+
+    /// Why the watching is impossible
+    pub description: String
 }
 
 pub mod events {
-    pub struct Terminated {}
+    use crate::actors::message::ControlMessage;
+    use crate::actors::abstract_actor_ref::ActorRef;
+    use crate::actors::error::Error;
+    use crate::actors::supervision::SupervisionStrategy;
+    use super::StopReason;
+
+    /// Watched actor was fully stopped
+    pub struct Terminated {
+
+        /// Terminated actor
+        pub actor: ActorRef,
+
+        /// Why actor was stopped
+        pub reason: StopReason
+    }
+
+    impl ControlMessage for Terminated {}
+
+    /// Watched actor was restarted by the supervision. Sent only to lifecycle watchers.
+    pub struct Restarted {
+
+        /// Restarted actor
+        pub actor: ActorRef,
+
+        /// Error which caused the restart
+        pub error: Error
+    }
+
+    /// Watched actor was failed at a message processing. Sent only to lifecycle watchers.
+    pub struct Failed {
+
+        /// Failed actor
+        pub actor: ActorRef,
+
+        /// Occurred error
+        pub error: Error,
+
+        /// Supervision action which will be applied to the actor
+        pub strategy: SupervisionStrategy
+    }
 }
 
 pub struct Watcher {
     feed: HashMap<ActorPath, (ActorRef, Vec<ActorRef>)>,
-    watchers: HashMap<ActorPath, u32>
+    watchers: HashMap<ActorPath, u32>,
+
+    /// Paths of the watchers, which is subscribed to the Restarted and Failed events of the
+    /// observed actor
+    lifecycle: HashMap<ActorPath, Vec<ActorPath>>,
+
+    /// Event stream of the actor system. All events is published to it.
+    event_stream: EventStream
 }
 
 impl Watcher {
-    pub fn new() -> Watcher {
+    pub fn new(event_stream: EventStream) -> Watcher {
         Watcher {
             feed: HashMap::new(),
-            watchers: HashMap::new()
+            watchers: HashMap::new(),
+            lifecycle: HashMap::new(),
+            event_stream
         }
     }
 
@@ -51,10 +130,24 @@ impl Watcher {
         }
     }
 
+    /// Subscribe the watcher actor to the events of the observed actor, including Restarted and
+    /// Failed events.
+    pub fn watch_lifecycle(&mut self, watcher: &ActorRef, observed: &ActorRef) {
+        self.watch(watcher, observed);
+
+        let obs_path = observed.path().clone();
+        if self.lifecycle.contains_key(&obs_path) {
+            self.lifecycle.get_mut(&obs_path).unwrap().push(watcher.path());
+        } else {
+            self.lifecycle.insert(obs_path, vec![watcher.path()]);
+        }
+    }
+
     /// Subscribe the watcher actor from the events of the observed actor
     pub fn unwatch(&mut self, watcher: &ActorRef, observed: &ActorRef) {
         trace!("{} unwatch {}", watcher, observed);
         let obs_path = observed.path().clone();
+        let mut removed = false;
         if self.feed.contains_key(&obs_path) {
 
             let (_, watchers) = self.feed.get_mut(&obs_path).unwrap();
@@ -67,6 +160,7 @@ impl Watcher {
                     new.push((*w).clone());
                 }
             }
+            removed = new.len() < watchers.len();
 
             if new.len() > 0 {
                 *watchers = new;
@@ -75,8 +169,16 @@ impl Watcher {
             }
         }
 
+        if let Some(paths) = self.lifecycle.get_mut(&obs_path) {
+            let wat_path = watcher.path();
+            paths.retain(|p| *p != wat_path);
+            if paths.len() == 0 {
+                self.lifecycle.remove(&obs_path);
+            }
+        }
+
         let wt_path = watcher.path();
-        if self.watchers.contains_key(&wt_path) {
+        if removed && self.watchers.contains_key(&wt_path) {
             let counter = self.watchers.get_mut(&wt_path).unwrap();
             *counter = *counter  - 1;
             if *counter <= 0 {
@@ -86,23 +188,31 @@ impl Watcher {
     }
 
     /// Registers some event from an actor. This operation cause to send corresponding message to
-    /// the all actors which was subscribed for source actor.
+    /// the all actors which was subscribed for source actor. Restarted and Failed events is sent
+    /// only to watchers subscribed through watch_lifecycle. All events is also published to the
+    /// event stream. Terminated actor is unsubscribed from the event stream.
     pub fn register_event(&mut self, from: &ActorRef, event: WatchingEvents) {
         let obs_path = from.path().clone();
         match event {
-            WatchingEvents::Terminated => {
-                trace!("Registered event 'Terminated' from {}", from);
+            WatchingEvents::Terminated(reason) => {
+                trace!("Registered event 'Terminated' from {} with reason {:?}", from, reason);
                 if self.feed.contains_key(&obs_path) {
                     let (observed, watchers) =
                         self.feed.get_mut(&obs_path).unwrap();
 
                     for aref in watchers {
                         trace!("Send Terminated event message from {} to {}", from, aref);
-                        aref.tell(msg!(events::Terminated {}), Some(&observed))
+                        let event = events::Terminated { actor: observed.clone(), reason: reason.clone() };
+                        aref.tell(msg!(event), Some(&observed))
                     }
 
                     self.feed.remove(&obs_path);
                 }
+                self.lifecycle.remove(&obs_path);
+
+                self.event_stream.unsubscribe_all(from);
+                let event = events::Terminated { actor: (*from).clone(), reason: reason.clone() };
+                self.event_stream.publish(msg!(event));
 
                 let wt_path = from.path();
                 if self.watchers.contains_key(&wt_path) {
@@ -117,7 +227,190 @@ impl Watcher {
                         self.unwatch(from, &obs);
                     }
                 }
+            },
+            WatchingEvents::Restarted(error) => {
+                trace!("Registered event 'Restarted' from {}", from);
+                for (observed, mut aref) in self.lifecycle_watchers(&obs_path) {
+                    let event = events::Restarted { actor: observed.clone(), error: error.clone() };
+                    aref.tell(msg!(event), Some(&observed))
+                }
+
+                let event = events::Restarted { actor: (*from).clone(), error };
+                self.event_stream.publish(msg!(event));
+            },
+            WatchingEvents::Failed(error, strategy) => {
+                trace!("Registered event 'Failed' from {}", from);
+                for (observed, mut aref) in self.lifecycle_watchers(&obs_path) {
+                    let event = events::Failed { actor: observed.clone(), error: error.clone(), strategy: strategy.clone() };
+                    aref.tell(msg!(event), Some(&observed))
+                }
+
+                let event = events::Failed { actor: (*from).clone(), error, strategy };
+                self.event_stream.publish(msg!(event));
             }
         }
     }
-}
\ No newline at end of file
+
+    /// Returns observed actor and his watchers subscribed to the lifecycle events
+    fn lifecycle_watchers(&self, obs_path: &ActorPath) -> Vec<(ActorRef, ActorRef)> {
+        let mut result = Vec::new();
+
+        if let (Some(paths), Some((observed, watchers))) = (self.lifecycle.get(obs_path), self.feed.get(obs_path)) {
+            for aref in watchers.iter() {
+                if paths.contains(&aref.path()) {
+                    result.push(((*observed).clone(), (*aref).clone()));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::prelude::*;
+    use crate::actors::actor::PoisonPill;
+    use crate::actors::watcher::{StopReason, LifecycleWatchError};
+    use crate::actors::watcher::events::{Terminated, Restarted, Failed};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::thread;
+
+    /// Fails on the "fail" message
+    struct Target;
+
+    impl Actor for Target {
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            match msg.get().downcast_ref::<&str>() {
+                Some(&"fail") => Err(err!("fail")),
+                _ => Ok(false)
+            }
+        }
+    }
+
+    /// Records received watching events
+    struct Observer {
+        log: Arc<Mutex<Vec<String>>>
+    }
+
+    impl Actor for Observer {
+        fn receive(&mut self, msg: Message, _ctx: ActorContext) -> HandleResult {
+            let msg = msg.get();
+            let event = if let Some(m) = msg.downcast_ref::<Terminated>() {
+                format!("terminated {} {:?}", m.actor.path(), m.reason)
+            } else if let Some(m) = msg.downcast_ref::<Restarted>() {
+                format!("restarted {}", m.actor.path())
+            } else if let Some(m) = msg.downcast_ref::<Failed>() {
+                format!("failed {} {:?}", m.actor.path(), m.strategy)
+            } else {
+                return Ok(false)
+            };
+            self.log.lock().unwrap().push(event);
+            Ok(true)
+        }
+    }
+
+    fn new_observer(system: &mut LocalActorSystem) -> (ActorRef, Arc<Mutex<Vec<String>>>) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let observer = system.actor_of(Props::new(tsafe!(Observer { log: log.clone() })), None);
+        (observer, log)
+    }
+
+    fn wait_len(log: &Arc<Mutex<Vec<String>>>, len: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < len && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    #[test]
+    fn terminated_carries_actor_and_stop_reason() {
+        let mut system = LocalActorSystem::new();
+        let (observer, log) = new_observer(&mut system);
+
+        let mut normal = system.actor_of(Props::new(tsafe!(Target)), Some("normal"));
+        let mut poisoned = system.actor_of(Props::new(tsafe!(Target)), Some("poisoned"));
+        let props = Props::new(tsafe!(Target)).with_supervision_strategy(SupervisionStrategy::Stop);
+        let mut failed = system.actor_of(props, Some("failed"));
+        for target in [&normal, &poisoned, &failed].iter() {
+            system.watch(&observer, target);
+        }
+
+        system.stop(&mut normal);
+        wait_len(&log, 1);
+        poisoned.tell(msg!(PoisonPill {}), None);
+        wait_len(&log, 2);
+        failed.tell(msg!("fail"), None);
+        wait_len(&log, 3);
+
+        assert_eq!(*log.lock().unwrap(), vec![
+            "terminated /root/normal Normal",
+            "terminated /root/poisoned PoisonPill",
+            "terminated /root/failed Supervision"]);
+
+        // Observer lives in the other system, because actors of the terminated system is stopped.
+        // Watcher identifies actors by path, so observer must not have the same path as some
+        // actor of the terminated system.
+        let mut other = LocalActorSystem::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let observer = other.actor_of(Props::new(tsafe!(Observer { log: log.clone() })), Some("observer"));
+        let target = system.actor_of(Props::new(tsafe!(Target)), Some("shutdown"));
+        system.watch(&observer, &target);
+
+        system.terminate();
+        wait_len(&log, 1);
+        assert_eq!(*log.lock().unwrap(), vec!["terminated /root/shutdown SystemShutdown"]);
+
+        other.terminate();
+    }
+
+    #[test]
+    fn restarted_and_failed_is_sent_only_to_lifecycle_watchers() {
+        let mut system = LocalActorSystem::new();
+        let (plain, plain_log) = new_observer(&mut system);
+        let (lifecycle, lifecycle_log) = new_observer(&mut system);
+
+        let mut target = system.actor_of(Props::new(tsafe!(Target)), Some("target"));
+        system.watch(&plain, &target);
+        assert!(system.watch_lifecycle(&lifecycle, &target).is_ok());
+
+        target.tell(msg!("fail"), None);
+        wait_len(&lifecycle_log, 2);
+        system.stop(&mut target);
+        wait_len(&lifecycle_log, 3);
+        wait_len(&plain_log, 1);
+
+        assert_eq!(*lifecycle_log.lock().unwrap(), vec![
+            "failed /root/target Restart",
+            "restarted /root/target",
+            "terminated /root/target Normal"]);
+        assert_eq!(*plain_log.lock().unwrap(), vec!["terminated /root/target Normal"]);
+
+        system.terminate();
+    }
+
+    /// Factory which implements only required methods
+    struct Minimal;
+
+    impl ActorRefFactory for Minimal {
+        fn actor_of(self: &mut Self, _props: Props, _name: Option<&str>) -> ActorRef { unimplemented!() }
+        fn actor_select(&mut self, _path: &str) -> Vec<ActorRef> { unimplemented!() }
+        fn stop(self: &mut Self, _aref: &mut ActorRef) { unimplemented!() }
+        fn dead_letters(self: &mut Self) -> ActorRef { unimplemented!() }
+        fn watch(&mut self, _watcher: &ActorRef, _observed: &ActorRef) {}
+        fn unwatch(&mut self, _watcher: &ActorRef, _observed: &ActorRef) {}
+    }
+
+    #[test]
+    fn lifecycle_watching_is_not_supported_by_default() {
+        let mut system = LocalActorSystem::new();
+        let target = system.actor_of(Props::new(tsafe!(Target)), None);
+
+        let result = Minimal.watch_lifecycle(&target, &target);
+        assert!(result.err().unwrap().is::<LifecycleWatchError>());
+
+        system.terminate();
+    }
+}
@@ -0,0 +1,73 @@
+//! Actor dispatcher with strategy - thread pool with work stealing between threads
+//!
+//! Each actor has preferred thread in the pool, but if this thread is busy, ready mailbox of the
+//! actor may be taken by any idle thread. Messages of a single actor is still processed one by one,
+//! because mailbox is planned for execution only once until it will be processed.
+use crate::executors::work_stealing_executor::WorkStealingExecutor;
+use crate::executors::executor::{Executor, ExecutorTask};
+use crate::actors::dispatcher::Dispatcher;
+use crate::actors::default_dispatcher::{DefaultDispatcher, DEFAULT_THROUGHPUT};
+use crate::actors::actor_cell::ActorCell;
+use crate::actors::envelope::Envelope;
+use crate::actors::mailbox::Mailbox;
+use crate::actors::actor::Actor;
+use crate::common::tsafe::TSafe;
+use std::any::Any;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct WorkStealingDispatcher {
+    executor: WorkStealingExecutor,
+    rounds: usize,
+    throughput: Arc<AtomicUsize>
+}
+
+impl WorkStealingDispatcher {
+    pub fn new(t_count: u32) -> WorkStealingDispatcher {
+        let executor = WorkStealingExecutor::new()
+            .set_threads_count(t_count as usize)
+            .run();
+        WorkStealingDispatcher {
+            executor,
+            rounds: 0,
+            throughput: Arc::new(AtomicUsize::new(DEFAULT_THROUGHPUT))
+        }
+    }
+}
+
+impl Executor for WorkStealingDispatcher {
+    fn execute(&mut self, f: ExecutorTask, options: Option<Box<Any>>) {
+        self.executor.execute(f, options)
+    }
+
+    fn stop(&mut self) {
+        self.executor.stop();
+    }
+}
+
+impl Dispatcher for WorkStealingDispatcher {
+
+    /// Message processing is identical to the default dispatcher. Difference only in the executor,
+    /// which may run planned mailbox on any thread.
+    fn dispatch(self: &mut Self, cell: TSafe<ActorCell>, bid: usize, mailbox: TSafe<Mailbox + Send>, _actor: TSafe<Actor + Send>, envelope: Envelope) {
+        let throughput = self.throughput.clone();
+        DefaultDispatcher::plan(&mut self.executor, &throughput, cell, bid, mailbox, envelope);
+    }
+
+    fn obtain_bid(self: &mut Self) -> usize {
+        if self.rounds == self.executor.get_threads_count() - 1 {
+            self.rounds = 0;
+        } else {
+            self.rounds = self.rounds + 1;
+        }
+
+        self.rounds
+    }
+
+    fn set_throughput(self: &mut Self, throughput: usize) {
+        if throughput == 0 {
+            panic!("Throughput of the dispatcher must be greater than zero");
+        }
+        self.throughput.store(throughput, Ordering::Relaxed);
+    }
+}
@@ -71,7 +71,7 @@ impl MyDispatcher {
                 if im.is_ok() {
                     im.ok().unwrap()
                 } else {
-                    let f = cell.lock().unwrap().fail(im.err().unwrap(), cell.clone());
+                    let f = cell.lock().unwrap().fail(im.err().unwrap(), Some(msg.clone()), cell.clone());
                     f();
                     true
                 }
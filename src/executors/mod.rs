@@ -3,6 +3,7 @@
 //! # Table of Contents
 //! 1. [Introduction](#introduction)
 //! 2. [ThreadPinnedExecutor](#threadpinnedexecutor)
+//! 3. [WorkStealingExecutor](#workstealingexecutor)
 //!
 //! # Introduction
 //!
@@ -45,6 +46,38 @@
 //! let f1 = Box::new( || { println!("Task on explicitly selected thread with id 6") });
 //! executor.execute(f1, Some( Box::new(TaskOptions { thread_id: Some(6) } )));
 //! ```
+//!
+//! Count of threads of this executor may be changed after it was started. Tasks from the queues of
+//! removed threads is moved to the rest threads. Tasks with thread id which is out of the range of
+//! the new pool, is executed on the thread 'thread_id % threads_count'. Also executor may change
+//! threads count himself, based on the load of the threads. Load is checked by the timer, so idle
+//! pool is shrunk even if no one task is passed to it:
+//!
+//! ```ignore
+//! let mut executor = ThreadPinnedExecutor::new()
+//!     .set_threads_count(2)
+//!     .set_resize_policy(ResizePolicy::new(2, 8))
+//!     .run();
+//!
+//! executor.resize(4);
+//! ```
+//!
+//! # WorkStealingExecutor
+//!
+//! This executor also creates thread pool with specified counts of threads, where each thread has
+//! it's own queue. But thread id passed in the task options is only preferred thread. If some thread
+//! has no tasks in it's queue, he steals tasks from the queues of others threads. This executor is
+//! useful when tasks is distributed between threads unevenly.
+//!
+//! ```ignore
+//! let mut executor = WorkStealingExecutor::new()
+//!     .set_threads_count(8)
+//!     .run();
+//!
+//! let f = Box::new( || { println!("Task preferably on thread with id 6") });
+//! executor.execute(f, Some( Box::new(TaskOptions { thread_id: Some(6) } )));
+//! ```
 
 pub mod executor;
-pub mod thread_pinned_executor;
\ No newline at end of file
+pub mod thread_pinned_executor;
+pub mod work_stealing_executor;
\ No newline at end of file
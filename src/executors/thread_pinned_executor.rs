@@ -19,6 +19,9 @@
 //!
 //! let f1 = Box::new( || { println!("Task on explicitly selected thread with id 6") });
 //! executor.execute(f1, Some( Box::new(TaskOptions { thread_id: Some(6) } )));
+//!
+//! // Change threads count at runtime
+//! executor.resize(4);
 //! ```
 
 use crate::executors::executor::{Executor, ExecutorTask};
@@ -27,12 +30,15 @@ use std::sync::{Mutex, Arc, Condvar};
 use std::any::Any;
 use std::collections::vec_deque::VecDeque;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use rand::{Rng};
 
 /// Thread queue type alias
 type Queue = TSafe<VecDeque<ExecutorTask>>;
 
+/// Maximum interval between two checks of the resize policy by the timer
+const RESIZE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Automatically thread selection strategy
 #[derive(Clone)]
 pub enum DistributionStrategy {
@@ -52,18 +58,76 @@ pub enum DistributionStrategy {
 /// Options which will may be passed with task
 pub struct TaskOptions {
 
-    /// Thread id on which task will be executed. Attention, out of range thread id will cause to
-    /// panic!
+    /// Thread id on which task will be executed. Out of range thread id is wrapped by the
+    /// threads count.
     pub thread_id: Option<usize>
 }
 
-pub struct ThreadPinnedExecutor {
+/// Policy of automatically threads count changing. Load of the pool is calculated as average count
+/// of tasks in the threads queues. If load is greater than grow threshold, one thread is added to
+/// the pool. If load is less or equal to the shrink threshold, one thread is removed from the pool.
+/// Count of threads is always stays in range from min to max.
+#[derive(Clone)]
+pub struct ResizePolicy {
+    /// Minimum threads count
+    pub min: usize,
+
+    /// Maximum threads count
+    pub max: usize,
+
+    /// Average queue length after which pool will be grown
+    pub grow_threshold: usize,
+
+    /// Average queue length after which pool will be shrunk
+    pub shrink_threshold: usize,
+
+    /// Minimum interval between two load checks
+    pub interval: Duration
+}
+
+impl ResizePolicy {
+
+    /// Creates new policy with specified range of threads count. Grow threshold = 10, shrink
+    /// threshold = 0, interval = 1 second.
+    pub fn new(min: usize, max: usize) -> ResizePolicy {
+        if min == 0 || min > max {
+            panic!("Invalid threads range of the resize policy - {}..{}", min, max);
+        }
+
+        ResizePolicy {
+            min,
+            max,
+            grow_threshold: 10,
+            shrink_threshold: 0,
+            interval: Duration::from_secs(1)
+        }
+    }
+
+    /// Set grow threshold
+    pub fn set_grow_threshold(mut self, threshold: usize) -> Self {
+        self.grow_threshold = threshold;
+        self
+    }
+
+    /// Set shrink threshold
+    pub fn set_shrink_threshold(mut self, threshold: usize) -> Self {
+        self.shrink_threshold = threshold;
+        self
+    }
+
+    /// Set minimum interval between load checks
+    pub fn set_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// Threads of the executor. Shared between the executor and the thread which checks the resize
+/// policy, so idle pool may be shrunk even if no one new task is passed to the executor.
+struct Pool {
     /// Total threads count
     threads_count: usize,
 
-    /// Implicit thread id selecting strategy
-    distribution_strategy: DistributionStrategy,
-
     /// Threads queues
     queues: Vec<Queue>,
 
@@ -73,6 +137,126 @@ pub struct ThreadPinnedExecutor {
     /// Threads stop flags
     stops: Vec<TSafe<bool>>,
 
+    /// Policy of automatically threads count changing
+    resize_policy: Option<ResizePolicy>,
+
+    /// Time of the last load check for the resize policy
+    last_resize_check: Instant,
+
+    /// Is executor was stopped
+    stopped: bool
+}
+
+impl Pool {
+
+    /// Changes threads count of the running pool. See ThreadPinnedExecutor::resize.
+    fn resize(&mut self, count: usize) {
+        if count == 0 {
+            panic!("Threads count of the executor must be greater than zero");
+        }
+
+        if self.queues.len() == 0 {
+            self.threads_count = count;
+            return;
+        }
+
+        while self.queues.len() < count {
+            self.spawn_thread();
+        }
+
+        while self.queues.len() > count {
+            let queue = self.queues.pop().unwrap();
+            let cvar = self.locks.pop().unwrap();
+            let stop = self.stops.pop().unwrap();
+
+            *stop.lock().unwrap() = true;
+            cvar.notify_all();
+
+            // Whole queue is moved to the thread which will now serve his thread id, so tasks
+            // pinned to the same thread id is still executed on the one thread in the same order
+            let thread_id = self.queues.len() % count;
+            let mut tasks: VecDeque<ExecutorTask> = queue.lock().unwrap().drain(..).collect();
+            if tasks.len() > 0 {
+                self.queues[thread_id].lock().unwrap().append(&mut tasks);
+                self.locks[thread_id].notify_one();
+            }
+        }
+
+        self.threads_count = count;
+    }
+
+    /// Creates new thread with his own queue and adds it to the end of the pool
+    fn spawn_thread(&mut self) {
+        let stop = tsafe!(false);
+        let queue = tsafe!(VecDeque::new());
+        let cvar = Arc::new(Condvar::new());
+        let mutex = Mutex::new(false);
+
+        self.queues.push(queue.clone());
+        self.locks.push(cvar.clone());
+        self.stops.push(stop.clone());
+
+        thread::spawn(move || {
+            while *stop.lock().unwrap() == false {
+                let f: Option<ExecutorTask> = {
+                    let mut q = queue.lock().unwrap();
+                    if q.len() > 0 {
+                        Some(q.pop_front().unwrap())
+                    } else {
+                        None
+                    }
+                };
+
+                if f.is_some() {
+                    f.unwrap()();
+                } else {
+                    cvar.wait_timeout(mutex.lock().unwrap(), Duration::from_millis(1000));
+                }
+            }
+
+            let mut q = queue.lock().unwrap();
+            q.clear();
+        });
+    }
+
+    /// Checks load of the threads and changes threads count in accordance with the resize policy.
+    /// Check is performed not often than the policy interval.
+    fn check_resize(&mut self) {
+        let (min, max, grow_threshold, shrink_threshold) = match &self.resize_policy {
+            Some(policy) => {
+                if self.last_resize_check.elapsed() < policy.interval {
+                    return;
+                }
+                (policy.min, policy.max, policy.grow_threshold, policy.shrink_threshold)
+            },
+            None => return
+        };
+
+        self.last_resize_check = Instant::now();
+
+        let mut total = 0;
+        for q in self.queues.iter() {
+            total = total + q.lock().unwrap().len();
+        }
+        let load = total / self.threads_count;
+
+        if load > grow_threshold && self.threads_count < max {
+            let count = self.threads_count + 1;
+            self.resize(count);
+        } else if load <= shrink_threshold && self.threads_count > min {
+            let count = self.threads_count - 1;
+            self.resize(count);
+        }
+    }
+}
+
+pub struct ThreadPinnedExecutor {
+    /// Threads of the executor
+    pool: TSafe<Pool>,
+
+    /// Implicit thread id selecting strategy
+    distribution_strategy: DistributionStrategy,
+
     /// Rounds counter for the Round distribution strategy
     rounds: usize
 }
@@ -84,23 +268,28 @@ impl ThreadPinnedExecutor {
     pub fn new() -> ThreadPinnedExecutor {
         let cpu_count = num_cpus::get();
         ThreadPinnedExecutor {
-            threads_count: cpu_count,
+            pool: tsafe!(Pool {
+                threads_count: cpu_count,
+                queues: Vec::new(),
+                locks: Vec::new(),
+                stops: Vec::new(),
+                resize_policy: None,
+                last_resize_check: Instant::now(),
+                stopped: false
+            }),
             distribution_strategy: DistributionStrategy::Round,
-            locks: Vec::new(),
-            queues: Vec::new(),
-            stops: Vec::new(),
             rounds: 0
         }
     }
 
     /// Set thread's count
-    pub fn set_threads_count(mut self, count: usize) -> Self {
-        self.threads_count = count;
+    pub fn set_threads_count(self, count: usize) -> Self {
+        self.pool.lock().unwrap().threads_count = count;
         self
     }
 
     pub fn get_threads_count(&self) -> usize {
-        self.threads_count
+        self.pool.lock().unwrap().threads_count
     }
 
     /// Set distribution strategy
@@ -109,56 +298,75 @@ impl ThreadPinnedExecutor {
         self
     }
 
-    /// Starts executor threads
-    pub fn run(mut self) -> Self {
-
-        for i in 0..self.threads_count {
-            let stop = tsafe!(false);
-            let queue = tsafe!(VecDeque::new());
-            let cvar = Arc::new(Condvar::new());
-            let mutex = Mutex::new(false);
-            let _tid = i;
-
-            self.queues.push(queue.clone());
-            self.locks.push(cvar.clone());
-            self.stops.push(stop.clone());
-
-            thread::spawn(move || {
-                while *stop.lock().unwrap() == false {
-                    let f: Option<ExecutorTask> = {
-                        let mut q = queue.lock().unwrap();
-                        if q.len() > 0 {
-                            Some(q.pop_front().unwrap())
-                        } else {
-                            None
-                        }
-                    };
+    /// Set policy of automatically threads count changing, based on the threads load
+    pub fn set_resize_policy(self, policy: ResizePolicy) -> Self {
+        self.pool.lock().unwrap().resize_policy = Some(policy);
+        self
+    }
 
-                    if f.is_some() {
-                        f.unwrap()();
-                    } else {
-                        cvar.wait_timeout(mutex.lock().unwrap(), Duration::from_millis(1000));
-                    }
-                }
+    /// Changes or disables resize policy of the running executor
+    pub fn update_resize_policy(&mut self, policy: Option<ResizePolicy>) {
+        let mut pool = self.pool.lock().unwrap();
+        pool.resize_policy = policy;
+        pool.last_resize_check = Instant::now();
+    }
+
+    /// Starts executor threads. Also starts the thread which checks the resize policy by the
+    /// timer, while executor is not stopped.
+    pub fn run(self) -> Self {
+        {
+            let mut pool = self.pool.lock().unwrap();
+            for _ in 0..pool.threads_count {
+                pool.spawn_thread();
+            }
 
-                let mut q = queue.lock().unwrap();
-                q.clear();
-            });
+            pool.last_resize_check = Instant::now();
         }
 
+        let pool = Arc::downgrade(&self.pool);
+        thread::spawn(move || {
+            loop {
+                let interval = match pool.upgrade() {
+                    Some(pool) => {
+                        let mut pool = pool.lock().unwrap();
+                        if pool.stopped {
+                            break;
+                        }
+                        pool.check_resize();
+                        match &pool.resize_policy {
+                            Some(policy) if policy.interval < RESIZE_CHECK_INTERVAL => policy.interval,
+                            _ => RESIZE_CHECK_INTERVAL
+                        }
+                    },
+                    None => break
+                };
+
+                thread::sleep(interval);
+            }
+        });
+
         self
+    }
 
+    /// Changes threads count of the running executor. New threads is added to the end of the
+    /// pool. Removed threads is taken from the end of the pool. Queue of each removed thread is
+    /// moved as whole to the thread with id 'removed_id % threads_count', which will execute tasks
+    /// of this thread id from now. Task which is executed by the removed thread at this moment will
+    /// be completed, after that thread will be stopped. Tasks with thread id out of range of the
+    /// new pool, will be executed on the thread with id 'thread_id % threads_count'.
+    pub fn resize(&mut self, count: usize) {
+        self.pool.lock().unwrap().resize(count);
     }
 
     /// Realizes implicit thread id selecting based on specified execution strategy
-    fn get_thread_id(&mut self, strategy: DistributionStrategy) -> usize {
+    fn get_thread_id(&mut self, pool: &Pool, strategy: DistributionStrategy) -> usize {
         match strategy {
             DistributionStrategy::Load => {
                 let mut min = 1000000000;
                 let mut min_q = 0;
                 let mut qn = 0;
 
-                for q in self.queues.iter() {
+                for q in pool.queues.iter() {
                     let len = q.lock().unwrap().len();
                     if len < min {
                         min = len;
@@ -170,7 +378,7 @@ impl ThreadPinnedExecutor {
                 min_q
             },
             DistributionStrategy::Round => {
-                if self.rounds == self.threads_count - 1 {
+                if self.rounds >= pool.threads_count - 1 {
                     self.rounds = 0;
                 } else {
                     self.rounds = self.rounds + 1;
@@ -179,7 +387,7 @@ impl ThreadPinnedExecutor {
                 self.rounds
             },
             DistributionStrategy::Random => {
-                rand::thread_rng().gen_range(0, self.threads_count - 1)
+                rand::thread_rng().gen_range(0, pool.threads_count - 1)
             },
             DistributionStrategy::EventLoop => {
                 0
@@ -193,6 +401,10 @@ impl Executor for ThreadPinnedExecutor {
     /// Plans task for execution. This method must be called only after the executor is running.
     /// Otherwise it will cause to panic!
     fn execute(&mut self, f: ExecutorTask, options: Option<Box<Any>>) {
+        let pool = self.pool.clone();
+        let mut pool = pool.lock().unwrap();
+        pool.check_resize();
+
         let thread_id = if options.is_some() {
             let options = options.unwrap();
             let options = options.downcast_ref::<TaskOptions>().unwrap();
@@ -200,25 +412,95 @@ impl Executor for ThreadPinnedExecutor {
             if options.thread_id.is_some() {
                 options.thread_id.unwrap()
             } else {
-                self.get_thread_id(self.distribution_strategy.clone())
+                self.get_thread_id(&pool, self.distribution_strategy.clone())
             }
         } else {
-           self.get_thread_id(self.distribution_strategy.clone())
+           self.get_thread_id(&pool, self.distribution_strategy.clone())
         };
 
-        self.queues[thread_id].lock().unwrap().push_back(f);
-        self.locks[thread_id].notify_one();
+        let thread_id = thread_id % pool.threads_count;
+        pool.queues[thread_id].lock().unwrap().push_back(f);
+        pool.locks[thread_id].notify_one();
 
     }
 
     /// Stops executor threads
     fn stop(&mut self) {
-        for stop in self.stops.iter() {
+        let mut pool = self.pool.lock().unwrap();
+        pool.stopped = true;
+
+        for stop in pool.stops.iter() {
             *stop.lock().unwrap() = true;
         }
 
-        for cvar in self.locks.iter() {
+        for cvar in pool.locks.iter() {
             cvar.notify_all();
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::executors::executor::Executor;
+    use crate::executors::thread_pinned_executor::{ThreadPinnedExecutor, TaskOptions, ResizePolicy};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn removed_queue_is_moved_whole_and_keeps_order() {
+        let mut executor = ThreadPinnedExecutor::new()
+            .set_threads_count(4)
+            .run();
+
+        // Thread 3 is blocked by the gate, so his tasks is still in the queue at resize
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let gate = Arc::new(Mutex::new(()));
+        let guard = gate.lock().unwrap();
+        {
+            let gate = gate.clone();
+            let f = Box::new(move || { let _gate = gate.lock().unwrap(); });
+            executor.execute(f, Some(Box::new(TaskOptions { thread_id: Some(3) })));
+        }
+        for i in 0..20 {
+            let log = log.clone();
+            let f = Box::new(move || { log.lock().unwrap().push((i, thread::current().id())); });
+            executor.execute(f, Some(Box::new(TaskOptions { thread_id: Some(3) })));
+        }
+
+        executor.resize(2);
+        drop(guard);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while log.lock().unwrap().len() < 20 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 20);
+        let order: Vec<u32> = log.iter().map(|(i, _)| *i).collect();
+        assert_eq!(order, (0..20).collect::<Vec<u32>>());
+        assert!(log.iter().all(|(_, tid)| *tid == log[0].1));
+
+        executor.stop();
+    }
+
+    #[test]
+    fn idle_pool_is_shrunk_without_new_tasks() {
+        let policy = ResizePolicy::new(1, 4)
+            .set_interval(Duration::from_millis(50));
+        let mut executor = ThreadPinnedExecutor::new()
+            .set_threads_count(4)
+            .set_resize_policy(policy)
+            .run();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while executor.get_threads_count() > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(executor.get_threads_count(), 1);
+
+        executor.stop();
+    }
+}
@@ -0,0 +1,249 @@
+//! Thread pool executor with work stealing
+//!
+//! In this implementation of executor, each thread has its own task queue, same as in the
+//! ThreadPinnedExecutor. But if some thread has no tasks in its own queue, he will steal tasks
+//! from the queues of other threads. Thread id passed in the task options is treated only as
+//! preferred thread, so task may be executed on any other thread of the pool.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! let mut executor = WorkStealingExecutor::new()
+//!     .set_threads_count(8)
+//!     .run();
+//!
+//! let f0 = Box::new( || { println!("Task on some thread") });
+//! executor.execute(f0, None);
+//!
+//! let f1 = Box::new( || { println!("Task preferably on thread with id 6") });
+//! executor.execute(f1, Some( Box::new(TaskOptions { thread_id: Some(6) } )));
+//! ```
+
+use crate::executors::executor::{Executor, ExecutorTask};
+use crate::executors::thread_pinned_executor::TaskOptions;
+use crate::common::tsafe::TSafe;
+use std::sync::{Mutex, Arc, Condvar};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::any::Any;
+use std::collections::vec_deque::VecDeque;
+use std::thread;
+use std::time::Duration;
+
+/// State shared between the executor and his threads
+struct Shared {
+
+    /// Threads queues
+    queues: Vec<TSafe<VecDeque<ExecutorTask>>>,
+
+    /// Count of tasks in all queues. Idle threads sleeps while it is zero.
+    pending: Mutex<usize>,
+
+    /// Notifier of new tasks
+    cvar: Condvar,
+
+    /// Stop flag of all threads
+    stopped: AtomicBool
+}
+
+impl Shared {
+
+    /// Takes task from the own queue of the thread. If it is empty, tries to steal task from the
+    /// tail of the others queues.
+    fn take(&self, tid: usize) -> Option<ExecutorTask> {
+        let count = self.queues.len();
+        let mut task = self.queues[tid].lock().unwrap().pop_front();
+
+        if task.is_none() {
+            for i in 1..count {
+                task = self.queues[(tid + i) % count].lock().unwrap().pop_back();
+                if task.is_some() {
+                    break;
+                }
+            }
+        }
+
+        if task.is_some() {
+            *self.pending.lock().unwrap() -= 1;
+        }
+
+        task
+    }
+}
+
+pub struct WorkStealingExecutor {
+    /// Total threads count
+    threads_count: usize,
+
+    /// State shared with threads
+    shared: Option<Arc<Shared>>,
+
+    /// Rounds counter used for tasks without preferred thread
+    rounds: usize
+}
+
+impl WorkStealingExecutor {
+
+    /// Creates new executor with default settings. Thread counts = count of the physical cpu
+    /// core's.
+    pub fn new() -> WorkStealingExecutor {
+        WorkStealingExecutor {
+            threads_count: num_cpus::get(),
+            shared: None,
+            rounds: 0
+        }
+    }
+
+    /// Set thread's count
+    pub fn set_threads_count(mut self, count: usize) -> Self {
+        self.threads_count = count;
+        self
+    }
+
+    pub fn get_threads_count(&self) -> usize {
+        self.threads_count
+    }
+
+    /// Starts executor threads
+    pub fn run(mut self) -> Self {
+        let mut queues = Vec::new();
+        for _ in 0..self.threads_count {
+            queues.push(tsafe!(VecDeque::new()));
+        }
+
+        let shared = Arc::new(Shared {
+            queues,
+            pending: Mutex::new(0),
+            cvar: Condvar::new(),
+            stopped: AtomicBool::new(false)
+        });
+
+        for tid in 0..self.threads_count {
+            let shared = shared.clone();
+
+            thread::spawn(move || {
+                while !shared.stopped.load(Ordering::Relaxed) {
+                    match shared.take(tid) {
+                        Some(mut f) => f(),
+                        None => {
+                            let pending = shared.pending.lock().unwrap();
+                            let _ = shared.cvar.wait_timeout_while(pending, Duration::from_millis(1000), |p| {
+                                *p == 0 && !shared.stopped.load(Ordering::Relaxed)
+                            });
+                        }
+                    }
+                }
+
+                shared.queues[tid].lock().unwrap().clear();
+            });
+        }
+
+        self.shared = Some(shared);
+        self
+    }
+}
+
+impl Executor for WorkStealingExecutor {
+
+    /// Plans task for execution. Task is placed to the queue of preferred thread, if it was
+    /// specified, or to the queue selected by round-robin counter. This method must be called only
+    /// after the executor is running. Otherwise it will cause to panic!
+    fn execute(&mut self, f: ExecutorTask, options: Option<Box<Any>>) {
+        let preferred = options
+            .and_then(|o| o.downcast_ref::<TaskOptions>().and_then(|o| o.thread_id));
+
+        let thread_id = match preferred {
+            Some(id) => id % self.threads_count,
+            None => {
+                self.rounds = (self.rounds + 1) % self.threads_count;
+                self.rounds
+            }
+        };
+
+        // Counter is incremented before the push, so it's never less than the real count of tasks
+        // and the thread which took the task can't decrement it below zero
+        let shared = self.shared.as_ref().unwrap();
+        *shared.pending.lock().unwrap() += 1;
+        shared.queues[thread_id].lock().unwrap().push_back(f);
+        shared.cvar.notify_one();
+    }
+
+    /// Stops executor threads
+    fn stop(&mut self) {
+        if let Some(shared) = self.shared.as_ref() {
+            shared.stopped.store(true, Ordering::Relaxed);
+            let _pending = shared.pending.lock().unwrap();
+            shared.cvar.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::executors::executor::Executor;
+    use crate::executors::thread_pinned_executor::TaskOptions;
+    use crate::executors::work_stealing_executor::WorkStealingExecutor;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn wait_count(counter: &Arc<AtomicUsize>, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while counter.load(Ordering::SeqCst) < count && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn all_tasks_is_executed_and_pending_returns_to_zero() {
+        let mut executor = WorkStealingExecutor::new()
+            .set_threads_count(4)
+            .run();
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        for i in 0..2000 {
+            let counter = counter.clone();
+            let f = Box::new(move || { counter.fetch_add(1, Ordering::SeqCst); });
+            // All tasks prefer the same thread, so others threads must steal them
+            let options = if i % 2 == 0 { Some(Box::new(TaskOptions { thread_id: Some(9) }) as Box<_>) } else { None };
+            executor.execute(f, options);
+        }
+
+        wait_count(&counter, 2000);
+        assert_eq!(counter.load(Ordering::SeqCst), 2000);
+
+        // Counter may be decremented by the worker slightly after the task was completed
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while *executor.shared.as_ref().unwrap().pending.lock().unwrap() != 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(*executor.shared.as_ref().unwrap().pending.lock().unwrap(), 0);
+
+        executor.stop();
+    }
+
+    #[test]
+    fn busy_thread_tasks_is_stolen() {
+        let mut executor = WorkStealingExecutor::new()
+            .set_threads_count(2)
+            .run();
+
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        // First task blocks thread 0, second task on the same thread must be stolen by thread 1
+        let blocker = Box::new(|| { thread::sleep(Duration::from_millis(2000)); });
+        executor.execute(blocker, Some(Box::new(TaskOptions { thread_id: Some(0) })));
+        thread::sleep(Duration::from_millis(50));
+
+        let started = Instant::now();
+        let counter2 = counter.clone();
+        let f = Box::new(move || { counter2.fetch_add(1, Ordering::SeqCst); });
+        executor.execute(f, Some(Box::new(TaskOptions { thread_id: Some(0) })));
+
+        wait_count(&counter, 1);
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        assert!(started.elapsed() < Duration::from_millis(1500));
+
+        executor.stop();
+    }
+}
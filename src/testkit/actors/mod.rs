@@ -5,7 +5,8 @@
 //! 2. [TestActorSystem](#testactorsystem)
 //! 3. [TestActorRef](#testactorref)
 //! 4. [TestProbe](#testprobe)
-//! 5. [Examples](#examples)
+//! 5. [CallingThreadDispatcher](#callingthreaddispatcher)
+//! 6. [Examples](#examples)
 //!
 //! # Introduction
 //!
@@ -269,6 +270,32 @@
 //! - expect_no_msg). Second - you must pack separate test cases in blocks of code, because when
 //! TestProbe will be dropped, he automatically unlocks condvar.
 //!
+//! # CallingThreadDispatcher
+//!
+//! By default, actors in the test actor system, processes messages on the threads of the default
+//! dispatcher. Because of this, after send a message, test must wait some time before checks the
+//! actor's state. For avoid this, test actor system registers special dispatcher with name
+//! 'calling_thread'. Actor which uses this dispatcher, processes messages synchronously on the
+//! thread which call tell. After tell call was returned, message is already processed:
+//!
+//! ```ignore
+//! let props = Props::new(tsafe!(SomeActor::new()))
+//!     .with_dispatcher("calling_thread");
+//! let mut target = system.actor_of(props, None);
+//!
+//! target.tell(msg!(commands::Increment { }), None);
+//!
+//! in_state! (target, SomeActor, actor => {
+//!     assert_eq!(actor.counter, 1);
+//! });
+//! ```
+//!
+//! If actor sends a messages from the receive method to itself or to other actors with this
+//! dispatcher, they will be processed right after processing of the current message will be
+//! completed, but before the outer tell will return. So actor never reenters to the receive method
+//! recursively. Pay attention, that actor which uses blocking operations in the receive method, will
+//! block the test thread.
+//!
 //! # Helper macros
 //!
 //! In the testkit exists few macros which simplify some aspects of testing.
@@ -64,8 +64,10 @@ impl AbstractActorRef for TestLocalActorRef {
         let cell_cloned = self.cell.clone();
         let path_cloned = self.path.clone();
         let toref = Box::new(TestLocalActorRef::new(cell_cloned, path_cloned));
-        let mut cell = self.cell.lock().unwrap();
-        cell.send(&self.cell, msg, rself.map_or(None, |v| Some((*v).clone())), toref);
+
+        // Messages for actors with the calling thread dispatcher is processed here, after the
+        // cell was released
+        ActorCell::send_and_run(&self.cell, msg, rself.map_or(None, |v| Some((*v).clone())), toref);
         // --------- end ----------
     }
 
@@ -12,8 +12,10 @@ use crate::actors::actor_cell::ActorCell;
 use crate::actors::actor::PoisonPill;
 use crate::actors::default_dispatcher::DefaultDispatcher;
 use crate::actors::pinned_dispatcher::PinnedDispatcher;
+use crate::actors::calling_thread_dispatcher::CallingThreadDispatcher;
 use crate::actors::dispatcher::Dispatcher;
-use crate::actors::dead_letters::DeadLetters;
+use crate::actors::mailbox::MailboxFactory;
+use crate::actors::dead_letters::{DeadLetters, DeadLetter};
 use crate::actors::synthetic_actor::SyntheticActor;
 use crate::actors::unbound_mailbox::UnboundMailbox;
 use crate::actors::actor_ref_factory::{ActorRefFactory, ActorSelectError};
@@ -21,15 +23,20 @@ use crate::actors::abstract_actor_system::AbstractActorSystem;
 use crate::testkit::actors::test_local_actor_ref::TestLocalActorRef;
 use crate::testkit::actors::test_probe::TestProbe;
 use crate::actors::abstract_actor_ref::ActorRef;
+use crate::actors::error::Error;
 use crate::actors::watcher::WatchingEvents;
 use crate::actors::watcher::Watcher;
+use crate::actors::event_stream::EventStream;
+use crate::actors::coordinated_shutdown::CoordinatedShutdown;
 use crate::actors::message::Message;
 use crate::actors::wrapped_dispatcher::WrappedDispatcher;
 use crate::executors::executor::Executor;
 use crate::actors::scheduler::Scheduler;
 use crate::actors::supervision::SupervisionStrategy;
 use crate::futures::future::{Future, WrappedFuture};
+use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::collections::hash_map::HashMap;
 use std::collections::vec_deque::VecDeque;
 
@@ -39,9 +46,15 @@ pub struct TestLocalActorSystem {
     // ------- mirror ---------
     nids: TSafe<usize>,
     dispatchers: TSafe<HashMap<String, TSafe<Dispatcher + Send>>>,
+    mailboxes: TSafe<HashMap<String, MailboxFactory>>,
     dead_letters: Option<ActorRef>,
     scheduler: TSafe<Scheduler>,
     watcher: TSafe<Watcher>,
+    event_stream: EventStream,
+    dead_letters_count: Arc<AtomicUsize>,
+    dead_letters_publisher: Sender<DeadLetter>,
+    log_unhandled: Arc<AtomicBool>,
+    coordinated_shutdown: TSafe<CoordinatedShutdown>,
     root: Option<TSafe<ActorCell>>,
     root_path: TSafe<ActorPath>,
     boxed_self: Option<TSafe<TestLocalActorSystem>>,
@@ -54,7 +67,8 @@ pub struct TestLocalActorSystem {
 impl TestLocalActorSystem {
 
     /// Identical to original in all expect than it will automatically starts system. No need call
-    /// run manually
+    /// run manually. Also registers dispatcher with name 'calling_thread', which processes messages
+    /// synchronously on the thread which sent them.
     pub fn new() -> TestLocalActorSystem {
 
         // ------- mirror ---------
@@ -64,16 +78,24 @@ impl TestLocalActorSystem {
         dispatchers.insert(String::from("default"), def_dispatch.clone());
 
         let root_path = tsafe!(ActorPath::new("root", None));
+        let event_stream = EventStream::new();
+        let dead_letters_publisher = DeadLetters::spawn_publisher(event_stream.clone());
 
 
         let mut system = TestLocalActorSystem {
             nids: tsafe!(0),
             dispatchers: tsafe!(dispatchers),
+            mailboxes: tsafe!(HashMap::new()),
             dead_letters: None,
             root: None,
             root_path: root_path.clone(),
             scheduler: tsafe!(Scheduler::new()),
-            watcher: tsafe!(Watcher::new()),
+            watcher: tsafe!(Watcher::new(event_stream.clone())),
+            event_stream,
+            dead_letters_count: Arc::new(AtomicUsize::new(0)),
+            dead_letters_publisher,
+            log_unhandled: Arc::new(AtomicBool::new(false)),
+            coordinated_shutdown: tsafe!(CoordinatedShutdown::new()),
             sub: tsafe!(None),
             boxed_self: None
         };
@@ -92,7 +114,7 @@ impl TestLocalActorSystem {
         let root_safe = tsafe!(root);
 
         let dlp = tsafe!(ActorPath::new("deadLetters", Some(root_path.clone())));
-        let dlm = DeadLetters::new();
+        let dlm = DeadLetters::new(dlp.lock().unwrap().clone(), system.dead_letters_publisher.clone(), system.dead_letters_count.clone());
         let mut dlc = ActorCell::new(
             system_safe.clone(),
             dlp.clone(),
@@ -114,9 +136,27 @@ impl TestLocalActorSystem {
         system.root = Some(root_safe);
         boxed_dlc.lock().unwrap().start(boxed_dlc.clone());
         system_safe.lock().unwrap().boxed_self = Some(system_safe.clone());
+        // --------- end ----------
+
+        system.add_dispatcher("calling_thread", tsafe!(CallingThreadDispatcher::new()));
+
+        // Weak handle, because the coordinated shutdown is owned by the system itself
+        let dispatchers = Arc::downgrade(&system.dispatchers);
+        system.coordinated_shutdown.lock().unwrap().set_terminator(move || {
+            if let Some(dispatchers) = dispatchers.upgrade() {
+                TestLocalActorSystem::stop_system(&dispatchers);
+            }
+        });
 
         system
-        // --------- end ----------
+    }
+
+    /// Stops all actors and dispatchers. Called by the coordinated shutdown after the last phase.
+    fn stop_system(dispatchers: &TSafe<HashMap<String, TSafe<Dispatcher + Send>>>) {
+        let d_list = dispatchers.lock().unwrap();
+        for (_, d) in d_list.iter() {
+            d.lock().unwrap().stop();
+        }
     }
 
     /// Create new TestProbe with specified name
@@ -150,7 +190,7 @@ impl ActorRefFactory for TestLocalActorSystem {
 
 
         // ------- mirror ---------
-        let mailbox = tsafe!(UnboundMailbox::new());
+        let mailbox = props.mailbox.create(&self.mailboxes.lock().unwrap());
 
         let mut aname: String;
 
@@ -183,7 +223,7 @@ impl ActorRefFactory for TestLocalActorSystem {
             }
         };
 
-        let cell = ActorCell::new(
+        let mut cell = ActorCell::new(
             tsafe!(self.clone()),
             path.clone(),
             props.actor,
@@ -193,6 +233,9 @@ impl ActorRefFactory for TestLocalActorSystem {
             self.root.clone(),
             props.supervision_strategy
         );
+        cell.supervisor_strategy = props.supervisor_strategy;
+        cell.restart_limit = props.restart_limit;
+        cell.factory = props.factory;
         let boxed_cell = tsafe!(cell);
 
         {
@@ -296,6 +339,13 @@ impl ActorRefFactory for TestLocalActorSystem {
         self.watcher.lock().unwrap().watch(watcher, observed);
     }
 
+    /// Register watcher for receive all 'watching events' from observed actor, including
+    /// Restarted and Failed events
+    fn watch_lifecycle(&mut self, watcher: &ActorRef, observed: &ActorRef) -> Result<(), Error> {
+        self.watcher.lock().unwrap().watch_lifecycle(watcher, observed);
+        Ok(())
+    }
+
     /// Unregister watcher from receive 'watching events' from observed actor
     fn unwatch(&mut self, watcher: &ActorRef, observed: &ActorRef) {
         self.watcher.lock().unwrap().unwatch(watcher, observed);
@@ -309,18 +359,39 @@ impl AbstractActorSystem for TestLocalActorSystem {
         self.scheduler.clone()
     }
 
+    /// Returns actor system event stream
+    fn event_stream(&self) -> EventStream {
+        self.event_stream.clone()
+    }
+
+    /// Returns total count of messages dropped to the dead letters
+    fn dead_letters_count(&self) -> usize {
+        self.dead_letters_count.load(Ordering::Relaxed)
+    }
+
+    /// Enables or disables logging of unhandled messages with warning level
+    fn set_log_unhandled(&mut self, enabled: bool) {
+        self.log_unhandled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Is unhandled messages is logged
+    fn is_log_unhandled(&self) -> bool {
+        self.log_unhandled.load(Ordering::Relaxed)
+    }
+
     /// Register new watching event from the specified actor
     fn register_watch_event(&self, from: &ActorRef, event: WatchingEvents) {
         self.watcher.lock().unwrap().register_event(&from, event);
     }
 
-    /// Stops the actor system
-    fn terminate(&mut self) {
-        let d_list = self.dispatchers.lock().unwrap();
-        for (_, d) in d_list.iter() {
-            d.lock().unwrap().stop();
-        }
-        self.dead_letters = None;
+    /// Stops the actor system through the coordinated shutdown without blocking of the caller
+    fn terminate_async(&mut self) -> WrappedFuture<(), ()> {
+        CoordinatedShutdown::run(&self.coordinated_shutdown)
+    }
+
+    /// Returns coordinated shutdown of the system
+    fn coordinated_shutdown(&self) -> TSafe<CoordinatedShutdown> {
+        self.coordinated_shutdown.clone()
     }
 
     /// Adds new dispatcher to the system. For now supporter only default dispatcher replacing
@@ -341,7 +412,7 @@ impl AbstractActorSystem for TestLocalActorSystem {
                 let root_safe = tsafe!(root);
 
                 let dlp = tsafe!(ActorPath::new("deadLetters", Some(self.root_path.clone())));
-                let dlm = DeadLetters::new();
+                let dlm = DeadLetters::new(dlp.lock().unwrap().clone(), self.dead_letters_publisher.clone(), self.dead_letters_count.clone());
                 let mut dlc = ActorCell::new(
                     tsafe!(self.clone()),
                     dlp.clone(),
@@ -387,6 +458,23 @@ impl AbstractActorSystem for TestLocalActorSystem {
         self.dispatchers.clone()
     }
 
+    /// Adds new mailbox factory to the system. Mailbox created by it may be specified for an actor
+    /// through Props::with_mailbox.
+    fn add_mailbox(&mut self, name: &str, factory: MailboxFactory) {
+        let name_str = String::from(name);
+        let mut mailboxes = self.mailboxes.lock().unwrap();
+
+        if !mailboxes.contains_key(&name_str) {
+            mailboxes.insert(name_str, factory);
+        } else {
+            panic!("Try to add mailbox with existed name '{}'", name_str)
+        }
+    }
+
+    fn get_mailboxes(&self) -> TSafe<HashMap<String, MailboxFactory>> {
+        self.mailboxes.clone()
+    }
+
     /// Returns dispatcher by name as executor
     fn get_executor(&self, name: &str) -> TSafe<Executor + Send> {
         tsafe!(WrappedDispatcher::new(self.get_dispatcher(name)))
@@ -420,11 +508,17 @@ impl Clone for TestLocalActorSystem {
         TestLocalActorSystem {
             nids: self.nids.clone(),
             dispatchers: self.dispatchers.clone(),
+            mailboxes: self.mailboxes.clone(),
             dead_letters: dead_letter, //self.dead_letters.clone()
             root,
             root_path: self.root_path.clone(),
             scheduler: self.scheduler.clone(),
             watcher: self.watcher.clone(),
+            event_stream: self.event_stream.clone(),
+            dead_letters_count: self.dead_letters_count.clone(),
+            dead_letters_publisher: self.dead_letters_publisher.clone(),
+            log_unhandled: self.log_unhandled.clone(),
+            coordinated_shutdown: self.coordinated_shutdown.clone(),
             sub: self.sub.clone(),
             boxed_self: self.boxed_self.clone()
         }